@@ -0,0 +1,145 @@
+// ANSI 16-color terminal palette derived from a generated palette
+// Colors keep their canonical hues, harmonized toward the primary color, and
+// are tone-matched to the surface of the current mode
+
+use crate::color::{self, TonalPalette};
+use crate::scheme::{self, ContrastCurve};
+use crate::theme::{tone_color_format, ColorFormat, Palette, PaletteOptions};
+
+/// Names of the 16 ANSI colors, in terminal order (`color0` to `color15`)
+pub const ANSI_NAMES: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright_black",
+    "bright_red",
+    "bright_green",
+    "bright_yellow",
+    "bright_blue",
+    "bright_magenta",
+    "bright_cyan",
+    "bright_white",
+];
+
+/// Index of an ANSI color name (`red`) or index name (`color1`)
+fn ansi_index(name: &str) -> Option<usize> {
+    let index = match name.strip_prefix("color") {
+        Some(index) => index.parse::<usize>().ok()?,
+        None => ANSI_NAMES.iter().position(|ansi_name| *ansi_name == name)?,
+    };
+    (index < ANSI_NAMES.len()).then_some(index)
+}
+
+/// Canonical colors (sRGB) whose hues the chromatic ANSI colors keep:
+/// red, green, yellow, blue, magenta and cyan
+const CANONICAL_COLORS: [[u8; 3]; 6] = [
+    [255, 0, 0],
+    [0, 255, 0],
+    [255, 255, 0],
+    [0, 0, 255],
+    [255, 0, 255],
+    [0, 255, 255],
+];
+
+/// Below this chroma the primary color has no meaningful hue to harmonize toward
+const MIN_HARMONIZE_CHROMA: f64 = 5.0;
+
+/// Chroma of the chromatic ANSI colors, high enough to tell them apart
+const ANSI_CHROMA: f64 = 48.0;
+
+/// Normal and bright colors are readable on the surface, like accent colors
+const CURVE_ANSI: ContrastCurve = scheme::CURVE_ACCENT;
+
+/// The 16 ANSI terminal colors
+#[derive(Debug, Default)]
+pub struct AnsiPalette {
+    colors: Vec<ColorFormat>,
+}
+
+impl AnsiPalette {
+    /// Generate the ANSI colors for a palette
+    ///
+    /// Black and white come from the neutral palette; the other colors keep
+    /// their canonical hue, rotated toward the primary hue by at most 15
+    /// degrees, at tones that meet the contrast level against the surface.
+    pub fn generate(palette: &Palette, is_dark_mode: bool, options: &PaletteOptions) -> Self {
+        let tone = |dark_tone: f64, light_tone: f64| {
+            if is_dark_mode {
+                dark_tone
+            } else {
+                light_tone
+            }
+        };
+        let surface = palette.surface.default.rgb();
+        let surface_tone = color::rgb_to_hct(surface.r, surface.g, surface.b).t;
+        let solve = |tone: f64| {
+            scheme::contrast_tone(
+                tone,
+                surface_tone,
+                &CURVE_ANSI,
+                options.contrast,
+                options.metric,
+            )
+        };
+        let normal_tone = solve(tone(70.0, 40.0));
+        let bright_tone = solve(tone(80.0, 50.0));
+
+        let primary = &palette.palettes.primary;
+        let source = primary.hct(50.0);
+        let chromatic: Vec<TonalPalette> = CANONICAL_COLORS
+            .iter()
+            .map(|&[r, g, b]| {
+                let hct = color::rgb_to_hct(r, g, b);
+                let hue = if primary.chroma >= MIN_HARMONIZE_CHROMA {
+                    color::harmonize(&hct, &source).h
+                } else {
+                    hct.h
+                };
+                TonalPalette::from_hue_and_chroma(hue, ANSI_CHROMA)
+            })
+            .collect();
+
+        let neutral = &palette.palettes.neutral;
+        let (black, bright_black) = (tone(20.0, 10.0), tone(50.0, 40.0));
+        let (white, bright_white) = (tone(80.0, 90.0), tone(95.0, 99.0));
+
+        let mut colors = Vec::with_capacity(ANSI_NAMES.len());
+        for (black, white, tone) in [
+            (black, white, normal_tone),
+            (bright_black, bright_white, bright_tone),
+        ] {
+            colors.push(tone_color_format(neutral, black));
+            colors.extend(
+                chromatic
+                    .iter()
+                    .map(|palette| tone_color_format(palette, tone)),
+            );
+            colors.push(tone_color_format(neutral, white));
+        }
+        Self { colors }
+    }
+
+    /// Look up a color by name (`red`, `bright_red`) or index (`color1`, `color9`)
+    pub fn get(&self, name: &str) -> Option<&ColorFormat> {
+        self.colors.get(ansi_index(name)?)
+    }
+
+    /// Replace a color by name or index, e.g. with one the theme sets
+    pub fn set(&mut self, name: &str, color: ColorFormat) -> Result<(), String> {
+        let slot = ansi_index(name)
+            .and_then(|index| self.colors.get_mut(index))
+            .ok_or_else(|| format!("ansi.{}: unknown ANSI color", name))?;
+        *slot = color;
+        Ok(())
+    }
+
+    /// The colors in terminal order, `color0` to `color15`
+    pub fn colors(&self) -> &[ColorFormat] {
+        &self.colors
+    }
+}
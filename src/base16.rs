@@ -0,0 +1,203 @@
+// Import and export of base16 and base24 color schemes
+// Both the tinted-theming format (`system`, `palette`) and the classic flat
+// format (`scheme`, `base00`...) are read; the tinted-theming format is written
+
+use crate::color;
+use crate::theme::Palette;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Slot names of a base16 scheme, base00 to base0F
+pub const BASE16_SLOTS: [&str; 16] = [
+    "base00", "base01", "base02", "base03", "base04", "base05", "base06", "base07", "base08",
+    "base09", "base0A", "base0B", "base0C", "base0D", "base0E", "base0F",
+];
+
+/// Extra slots of a base24 scheme: darker backgrounds and bright accents
+pub const BASE24_SLOTS: [&str; 8] = [
+    "base10", "base11", "base12", "base13", "base14", "base15", "base16", "base17",
+];
+
+/// The slots of a scheme, if the data is a base16 or base24 scheme
+fn scheme_slots(data: &Value) -> Option<&Map<String, Value>> {
+    let data = data.as_object()?;
+    let slots = match data.get("palette").and_then(Value::as_object) {
+        Some(palette) if data.contains_key("system") => palette,
+        _ => data,
+    };
+    slots.contains_key("base00").then_some(slots)
+}
+
+/// Whether theme data is a base16 or base24 scheme rather than a tinct theme
+pub fn is_base16_scheme(data: &Value) -> bool {
+    scheme_slots(data).is_some()
+}
+
+/// Convert a base16 or base24 scheme into tinct theme data
+///
+/// The scheme fills the mode of its variant (taken from `variant`, or from the
+/// tone of base00 when missing). Accents map to roles: base0D to primary,
+/// base0C to secondary, base0E to tertiary and base08 to error. The grayscale
+/// ramp gives the surfaces, text and outline, and the terminal colors follow
+/// the usual base16-shell and base24 layouts. base10 and base11 have no
+/// matching role and are not used.
+pub fn theme_from_base16(data: &Value) -> Result<Value, String> {
+    let slots = scheme_slots(data).ok_or("Not a base16 or base24 scheme: base00 is missing")?;
+    let slot = |name: &str| {
+        let value = slots
+            .get(name)
+            .ok_or_else(|| format!("Scheme is missing {}", name))?;
+        let hex = value
+            .as_str()
+            .ok_or_else(|| format!("{}: expected a color, got {}", name, value))?;
+        // Classic schemes write hex colors without the '#'
+        let rgba = color::parse_color(hex).map_err(|e| format!("{}: {}", name, e))?;
+        let rgb = rgba.rgb();
+        Ok::<_, String>(Value::String(color::rgb_to_hex(
+            rgb.r as f64,
+            rgb.g as f64,
+            rgb.b as f64,
+        )))
+    };
+
+    let is_base24 = data.get("system").and_then(Value::as_str) == Some("base24")
+        || slots.contains_key("base12");
+    let variant = match data.get("variant").and_then(Value::as_str) {
+        Some(variant @ ("dark" | "light")) => variant.to_string(),
+        Some(variant) => return Err(format!("variant: expected dark or light, got {}", variant)),
+        None => {
+            let background = color::parse_color(slot("base00")?.as_str().unwrap_or_default())?;
+            let background = background.rgb();
+            let tone = color::rgb_to_hct(background.r, background.g, background.b).t;
+            if tone < 50.0 { "dark" } else { "light" }.to_string()
+        }
+    };
+
+    // Terminal colors: base24 has its own bright accents and foreground
+    let (white, bright_black, bright) = if is_base24 {
+        (
+            "base06",
+            "base02",
+            ["base12", "base14", "base13", "base16", "base17", "base15"],
+        )
+    } else {
+        (
+            "base05",
+            "base03",
+            ["base08", "base0B", "base0A", "base0D", "base0E", "base0C"],
+        )
+    };
+    let mut ansi = Map::new();
+    ansi.insert("black".to_string(), slot("base00")?);
+    for (name, base) in ["red", "green", "yellow", "blue", "magenta", "cyan"]
+        .into_iter()
+        .zip(["base08", "base0B", "base0A", "base0D", "base0E", "base0C"])
+    {
+        ansi.insert(name.to_string(), slot(base)?);
+    }
+    ansi.insert("white".to_string(), slot(white)?);
+    ansi.insert("bright_black".to_string(), slot(bright_black)?);
+    for (name, base) in [
+        "bright_red",
+        "bright_green",
+        "bright_yellow",
+        "bright_blue",
+        "bright_magenta",
+        "bright_cyan",
+    ]
+    .into_iter()
+    .zip(bright)
+    {
+        ansi.insert(name.to_string(), slot(base)?);
+    }
+    ansi.insert("bright_white".to_string(), slot("base07")?);
+
+    let mode = serde_json::json!({
+        "primary": slot("base0D")?,
+        "secondary": slot("base0C")?,
+        "tertiary": slot("base0E")?,
+        "error": slot("base08")?,
+        "surface": slot("base00")?,
+        "background": slot("base00")?,
+        "surface_variant": slot("base01")?,
+        "on_surface": slot("base05")?,
+        "on_background": slot("base05")?,
+        "on_surface_variant": slot("base04")?,
+        "outline": slot("base03")?,
+        "ansi": ansi,
+    });
+    Ok(serde_json::json!({ variant: mode }))
+}
+
+/// A scheme in the tinted-theming YAML format
+#[derive(Serialize)]
+struct SchemeFile<'a> {
+    system: &'a str,
+    name: &'a str,
+    author: &'a str,
+    variant: &'a str,
+    palette: BTreeMap<&'static str, String>,
+}
+
+/// Write a generated palette as a base16 scheme in YAML
+///
+/// The grayscale ramp runs from the surface to the strongest text color,
+/// base0D, base0C, base0E and base08 are the primary, secondary, tertiary and
+/// error colors, and the other accents come from the ANSI colors.
+pub fn to_base16_yaml(palette: &Palette, name: &str, is_dark_mode: bool) -> Result<String, String> {
+    let ansi = |name: &str| {
+        palette
+            .ansi
+            .get(name)
+            .map(|color| color.rgb())
+            .ok_or_else(|| format!("Palette has no ANSI {} color", name))
+    };
+    let hex = |rgb: &color::Rgb| color::rgb_to_hex(rgb.r as f64, rgb.g as f64, rgb.b as f64);
+
+    // Orange sits between red and yellow; brown is a darker, grayer orange
+    let red = ansi("red")?;
+    let orange = color::blend_hct_hue(&red, &ansi("yellow")?, 0.5);
+    let orange_hct = color::rgb_to_hct(orange.r, orange.g, orange.b);
+    let brown = color::Hct::from_hct(orange_hct.h, orange_hct.c * 0.6, orange_hct.t - 10.0);
+
+    // Backgrounds step away from the surface by MD3's container offsets,
+    // so the ramp stays in order even when the theme sets its own surface
+    let neutral = &palette.palettes.neutral;
+    let surface = palette.surface.default.rgb();
+    let surface_tone = color::rgb_to_hct(surface.r, surface.g, surface.b).t;
+    let [base01, base02, base06, base07] = if is_dark_mode {
+        [surface_tone + 6.0, surface_tone + 16.0, 95.0, 99.0]
+    } else {
+        [surface_tone - 4.0, surface_tone - 8.0, 6.0, 2.0]
+    }
+    .map(|tone| neutral.hex(tone.clamp(0.0, 100.0)));
+
+    let colors = [
+        palette.surface.default.hex.clone(),
+        base01,
+        base02,
+        palette.outline.default.hex.clone(),
+        palette.on_surface_variant.default.hex.clone(),
+        palette.on_surface.default.hex.clone(),
+        base06,
+        base07,
+        palette.error.default.hex.clone(),
+        hex(&orange),
+        hex(&ansi("yellow")?),
+        hex(&ansi("green")?),
+        palette.secondary.default.hex.clone(),
+        palette.primary.default.hex.clone(),
+        palette.tertiary.default.hex.clone(),
+        brown.to_hex(),
+    ];
+
+    let scheme = SchemeFile {
+        system: "base16",
+        name,
+        author: "tinct",
+        variant: if is_dark_mode { "dark" } else { "light" },
+        palette: BASE16_SLOTS.into_iter().zip(colors).collect(),
+    };
+    serde_norway::to_string(&scheme).map_err(|e| format!("Could not write base16 scheme: {}", e))
+}
@@ -0,0 +1,261 @@
+// Checks run on a generated palette
+
+use crate::color::{self, ColorDeficiency, CvdSimulation, Lab};
+use crate::scheme::{self, ContrastCurve, ContrastMetric};
+use crate::theme::{ColorEntry, ColorFormat, Palette};
+
+/// Colors closer than this (CIEDE2000) are hard to tell apart at a glance
+pub const MIN_DISTINCT_DELTA_E: f64 = 10.0;
+
+/// Two roles that look alike with a color vision deficiency but not with normal vision
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollapsedPair {
+    pub first: String,
+    pub second: String,
+    /// Color difference with normal vision
+    pub delta_e: f64,
+    /// Color difference as seen with the deficiency
+    pub simulated_delta_e: f64,
+}
+
+/// An on-color that falls short of its contrast target on its background
+#[derive(Debug, Clone, PartialEq)]
+pub struct LowContrastPair {
+    pub foreground: String,
+    pub background: String,
+    /// Contrast in the checked metric
+    pub contrast: f64,
+    /// Target the pair should reach in the checked metric
+    pub target: f64,
+}
+
+/// Text role, background role and contrast curve of each checked pair
+type TextRolePair<'a> = (
+    String,
+    String,
+    &'a ColorFormat,
+    &'a ColorFormat,
+    ContrastCurve,
+);
+
+/// Text roles with the background they are drawn on and their contrast curve
+///
+/// Fixed on-colors are checked on the dim fixed color, the darker of the two.
+fn text_role_pairs(palette: &Palette) -> Vec<TextRolePair<'_>> {
+    fn pair<'a>(
+        foreground: &str,
+        text: &'a ColorEntry,
+        background: &str,
+        color: &'a ColorEntry,
+    ) -> TextRolePair<'a> {
+        let curve = if foreground.ends_with("variant") {
+            scheme::CURVE_ON_VARIANT
+        } else {
+            scheme::CURVE_ON_COLOR
+        };
+        (
+            foreground.to_string(),
+            background.to_string(),
+            &text.default,
+            &color.default,
+            curve,
+        )
+    }
+
+    let mut pairs = Vec::new();
+    let roles = [
+        ("primary", &palette.primary, &palette.on_primary),
+        ("secondary", &palette.secondary, &palette.on_secondary),
+        ("tertiary", &palette.tertiary, &palette.on_tertiary),
+        ("error", &palette.error, &palette.on_error),
+        (
+            "primary_container",
+            &palette.primary_container,
+            &palette.on_primary_container,
+        ),
+        (
+            "secondary_container",
+            &palette.secondary_container,
+            &palette.on_secondary_container,
+        ),
+        (
+            "tertiary_container",
+            &palette.tertiary_container,
+            &palette.on_tertiary_container,
+        ),
+        (
+            "error_container",
+            &palette.error_container,
+            &palette.on_error_container,
+        ),
+        ("background", &palette.background, &palette.on_background),
+        ("surface", &palette.surface, &palette.on_surface),
+        (
+            "surface_variant",
+            &palette.surface_variant,
+            &palette.on_surface_variant,
+        ),
+    ];
+    for (name, color, text) in roles {
+        pairs.push(pair(&format!("on_{}", name), text, name, color));
+    }
+    pairs.push(pair(
+        "inverse_on_surface",
+        &palette.inverse_on_surface,
+        "inverse_surface",
+        &palette.inverse_surface,
+    ));
+
+    let fixed = [
+        (
+            "primary",
+            &palette.primary_fixed_dim,
+            &palette.on_primary_fixed,
+            &palette.on_primary_fixed_variant,
+        ),
+        (
+            "secondary",
+            &palette.secondary_fixed_dim,
+            &palette.on_secondary_fixed,
+            &palette.on_secondary_fixed_variant,
+        ),
+        (
+            "tertiary",
+            &palette.tertiary_fixed_dim,
+            &palette.on_tertiary_fixed,
+            &palette.on_tertiary_fixed_variant,
+        ),
+    ];
+    for (name, fixed_dim, text, variant_text) in fixed {
+        let background = format!("{}_fixed_dim", name);
+        pairs.push(pair(
+            &format!("on_{}_fixed", name),
+            text,
+            &background,
+            fixed_dim,
+        ));
+        pairs.push(pair(
+            &format!("on_{}_fixed_variant", name),
+            variant_text,
+            &background,
+            fixed_dim,
+        ));
+    }
+
+    for custom in &palette.custom {
+        let [color, on_color, container, on_container] = custom.roles();
+        for ((foreground, text), (background, color)) in
+            [(on_color, color), (on_container, container)]
+        {
+            pairs.push((foreground, background, text, color, scheme::CURVE_ON_COLOR));
+        }
+    }
+    pairs
+}
+
+/// Find on-colors that fall below the minimum contrast under a contrast metric
+///
+/// The minimum is the lowest target of the role's contrast curve, 4.5:1 for
+/// text and 3:1 for secondary text, converted to the metric with
+/// `ContrastMetric::target`. Colors a theme sets by hand are checked the same
+/// way as generated ones.
+pub fn low_contrast_pairs(palette: &Palette, metric: ContrastMetric) -> Vec<LowContrastPair> {
+    // Solved tones are rounded to 8-bit colors, which may cost a little contrast
+    const TOLERANCE: f64 = 0.98;
+
+    text_role_pairs(palette)
+        .into_iter()
+        .filter_map(
+            |(foreground, background, foreground_color, background_color, curve)| {
+                let target = metric.target(curve.low);
+                let contrast = metric.contrast(&foreground_color.rgb(), &background_color.rgb());
+                (contrast < target * TOLERANCE).then_some(LowContrastPair {
+                    foreground,
+                    background,
+                    contrast,
+                    target,
+                })
+            },
+        )
+        .collect()
+}
+
+/// Role pairs that should stay distinguishable from each other
+///
+/// Custom colors are compared with the accents, and their containers with
+/// the accent containers.
+fn distinct_role_pairs(palette: &Palette) -> Vec<(String, String, &ColorFormat, &ColorFormat)> {
+    let mut accents = vec![
+        ("primary".to_string(), &palette.primary.default),
+        ("secondary".to_string(), &palette.secondary.default),
+        ("tertiary".to_string(), &palette.tertiary.default),
+        ("error".to_string(), &palette.error.default),
+    ];
+    let mut containers = vec![
+        (
+            "primary_container".to_string(),
+            &palette.primary_container.default,
+        ),
+        (
+            "secondary_container".to_string(),
+            &palette.secondary_container.default,
+        ),
+        (
+            "tertiary_container".to_string(),
+            &palette.tertiary_container.default,
+        ),
+        (
+            "error_container".to_string(),
+            &palette.error_container.default,
+        ),
+    ];
+    for custom in &palette.custom {
+        let [color, _, container, _] = custom.roles();
+        accents.push(color);
+        containers.push(container);
+    }
+
+    let mut pairs = Vec::new();
+    for group in [&accents, &containers] {
+        for (i, (first, first_color)) in group.iter().enumerate() {
+            for (second, second_color) in &group[i + 1..] {
+                pairs.push((first.clone(), second.clone(), *first_color, *second_color));
+            }
+        }
+    }
+    pairs
+}
+
+/// Find role pairs that collapse into similar colors with a color vision deficiency
+///
+/// Only pairs that are distinct with normal vision are reported, so roles the
+/// theme deliberately made alike are not flagged.
+pub fn collapsed_role_pairs(
+    palette: &Palette,
+    deficiency: ColorDeficiency,
+    severity: f64,
+) -> Vec<CollapsedPair> {
+    let lab = |color: &ColorFormat| Lab::from_rgb(&color.rgb());
+    let simulation = CvdSimulation {
+        deficiency,
+        severity,
+    };
+    let simulated_lab = |color: &ColorFormat| Lab::from_rgb(&simulation.apply(&color.rgb()));
+
+    distinct_role_pairs(palette)
+        .into_iter()
+        .filter_map(|(first, second, first_color, second_color)| {
+            let delta_e = color::delta_e_2000(&lab(first_color), &lab(second_color));
+            let simulated_delta_e =
+                color::delta_e_2000(&simulated_lab(first_color), &simulated_lab(second_color));
+            (delta_e >= MIN_DISTINCT_DELTA_E && simulated_delta_e < MIN_DISTINCT_DELTA_E).then_some(
+                CollapsedPair {
+                    first,
+                    second,
+                    delta_e,
+                    simulated_delta_e,
+                },
+            )
+        })
+        .collect()
+}
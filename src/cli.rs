@@ -1,21 +1,34 @@
-use clap::Parser;
+use clap::{ArgGroup, Parser};
 use std::env;
 use std::fs;
 use std::path::Path;
 
-use crate::config::ConfigSection;
-use crate::theme;
+use tinct::config::{Config, ConfigSection};
+use tinct::pywal::{self, PywalColors};
+use tinct::scheme::{self, ContrastLevel};
+use tinct::theme::{self, PaletteAdjustments, PaletteOptions};
+use tinct::theme_file::Theme;
+use tinct::{base16, color};
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
+#[command(group(ArgGroup::new("source").required(true).args(["theme", "image", "color"])))]
 pub struct CliArgs {
     /// Path to the TOML config file
     #[arg(short, long)]
     pub config: Option<String>,
 
-    /// Path to theme.json file or theme name in themes/ folder
-    #[arg(short, long, required = true)]
-    pub theme: String,
+    /// Path to a theme file (JSON, TOML or YAML) or theme name in themes/ folder
+    #[arg(short, long)]
+    pub theme: Option<String>,
+
+    /// Wallpaper image (PNG or JPEG) to take the source color from, instead of a theme
+    #[arg(short, long)]
+    pub image: Option<String>,
+
+    /// Source color (e.g. "#6750a4") to build both modes from, instead of a theme
+    #[arg(long)]
+    pub color: Option<String>,
 
     /// Theme mode override
     #[arg(short, long, value_enum, default_value = "dark")]
@@ -25,6 +38,46 @@ pub struct CliArgs {
     #[arg(short, long)]
     pub preview: bool,
 
+    /// Dynamic color scheme variant (overrides the config file)
+    #[arg(short, long, value_enum)]
+    pub scheme: Option<scheme::Scheme>,
+
+    /// Contrast level: standard, medium, high or a number from -1.0 to 1.0
+    #[arg(long, allow_hyphen_values = true)]
+    pub contrast: Option<ContrastLevel>,
+
+    /// Contrast metric used when solving on-colors (overrides the config file)
+    #[arg(long, value_enum)]
+    pub contrast_metric: Option<scheme::ContrastMetric>,
+
+    /// Degrees added to the hue of every theme color (overrides the config file and theme)
+    #[arg(long, allow_hyphen_values = true)]
+    pub hue_shift: Option<f64>,
+
+    /// Factor the chroma of every theme color is multiplied by (overrides the config file and theme)
+    #[arg(long, value_parser = parse_chroma_scale)]
+    pub chroma_scale: Option<f64>,
+
+    /// Tones added to the tone of every theme color (overrides the config file and theme)
+    #[arg(long, allow_hyphen_values = true)]
+    pub tone_offset: Option<f64>,
+
+    /// Also write the palette of the selected mode as a base16 scheme (YAML) to a file
+    #[arg(long, value_name = "FILE")]
+    pub export_base16: Option<String>,
+
+    /// Also write pywal's colors.json and colors.sh to a cache directory (defaults to ~/.cache/wal)
+    #[arg(long, value_name = "DIR", num_args = 0..=1, default_missing_value = pywal::DEFAULT_CACHE_DIR)]
+    pub pywal: Option<String>,
+
+    /// Preview the colors as seen with a color vision deficiency (implies --preview)
+    #[arg(long, value_enum)]
+    pub simulate: Option<color::ColorDeficiency>,
+
+    /// Severity of the simulated deficiency, from 0.0 (normal vision) to 1.0
+    #[arg(long, value_parser = parse_severity, default_value_t = 1.0, requires = "simulate")]
+    pub severity: f64,
+
     /// Logging level: quiet, normal, verbose
     #[arg(long, value_enum, default_value = "normal")]
     pub log_level: LogLevel,
@@ -45,75 +98,44 @@ impl std::fmt::Display for ThemeMode {
     }
 }
 
-#[derive(clap::ValueEnum, Clone, Debug, PartialEq)]
-pub enum LogLevel {
-    Quiet,
-    Normal,
-    Verbose,
-}
-
-// Functions to resolve paths
-pub fn resolve_path(
-    path: Option<&str>,
-    default_file: Option<&str>,
-    subfolder: Option<&str>,
-) -> Option<String> {
-    let script_dir = Path::new(env!("CARGO_MANIFEST_DIR")).to_str().unwrap();
-
-    // Handle default file
-    if path.is_none() {
-        if let Some(default) = default_file {
-            return Some(
-                Path::new(script_dir)
-                    .join(default)
-                    .to_string_lossy()
-                    .to_string(),
-            );
-        } else {
-            return None;
-        }
-    }
-
-    let path_str = path.unwrap();
-
-    // Check if path contains separators, if not, look in subfolder
-    if let Some(subfolder_name) = subfolder {
-        if !path_str.contains('/') && !path_str.contains('\\') {
-            let full_path = Path::new(script_dir)
-                .join(subfolder_name)
-                .join(format!("{}.json", path_str));
-
-            if full_path.exists() {
-                return Some(full_path.to_string_lossy().to_string());
-            }
-        }
+fn parse_severity(value: &str) -> Result<f64, String> {
+    let severity: f64 = value
+        .parse()
+        .map_err(|_| format!("Invalid severity '{}'", value))?;
+    if !(0.0..=1.0).contains(&severity) {
+        return Err(format!(
+            "Severity must be between 0.0 and 1.0, got {}",
+            severity
+        ));
     }
+    Ok(severity)
+}
 
-    // Check if path exists as provided
-    let expanded_path = shellexpand::tilde(path_str).to_string();
-    if Path::new(&expanded_path).exists() {
-        return Some(
-            Path::new(&expanded_path)
-                .canonicalize()
-                .ok()
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or(expanded_path),
-        );
+fn parse_chroma_scale(value: &str) -> Result<f64, String> {
+    let scale: f64 = value
+        .parse()
+        .map_err(|_| format!("Invalid chroma scale '{}'", value))?;
+    PaletteAdjustments {
+        chroma_scale: Some(scale),
+        ..Default::default()
     }
+    .validate()?;
+    Ok(scale)
+}
 
-    // Try as relative to script directory
-    let relative_path = Path::new(script_dir).join(path_str);
-    if relative_path.exists() {
-        return Some(
-            relative_path
-                .canonicalize()
-                .ok()
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or(relative_path.to_string_lossy().to_string()),
-        );
-    }
+/// Color vision deficiency simulation requested on the command line
+pub fn cvd_simulation(args: &CliArgs) -> Option<color::CvdSimulation> {
+    args.simulate.map(|deficiency| color::CvdSimulation {
+        deficiency,
+        severity: args.severity,
+    })
+}
 
-    None
+#[derive(clap::ValueEnum, Clone, Debug, PartialEq)]
+pub enum LogLevel {
+    Quiet,
+    Normal,
+    Verbose,
 }
 
 // Hook execution functions
@@ -137,26 +159,26 @@ pub fn run_post_hook(
 
         if post_hook_path.exists() && is_executable(&post_hook_path) {
             if let Some(name) = section_name {
-                crate::log::hook::executing(name);
+                tinct::log::hook::executing(name);
             }
 
             match std::process::Command::new(&post_hook_path).output() {
                 Ok(result) => {
                     if result.status.success() {
                         if let Some(name) = section_name {
-                            crate::log::hook::success(name);
+                            tinct::log::hook::success(name);
                         }
                         true
                     } else {
                         if let Some(name) = section_name {
-                            crate::log::error::message(name, "Error executing hook script");
+                            tinct::log::error::message(name, "Error executing hook script");
                         }
                         false
                     }
                 }
                 Err(e) => {
                     if let Some(name) = section_name {
-                        crate::log::error::message(
+                        tinct::log::error::message(
                             name,
                             &format!("Error executing hook script: {}", e),
                         );
@@ -166,7 +188,7 @@ pub fn run_post_hook(
             }
         } else {
             if let Some(name) = section_name {
-                crate::log::error::message(
+                tinct::log::error::message(
                     name,
                     &format!(
                         "post_hook '{}' not found. Skipping.",
@@ -179,7 +201,7 @@ pub fn run_post_hook(
     } else {
         // Handle command execution
         if let Some(name) = section_name {
-            crate::log::hook::executing(name);
+            tinct::log::hook::executing(name);
         }
 
         match std::process::Command::new("sh")
@@ -190,12 +212,12 @@ pub fn run_post_hook(
             Ok(result) => {
                 if result.status.success() {
                     if let Some(name) = section_name {
-                        crate::log::hook::success(name);
+                        tinct::log::hook::success(name);
                     }
                     true
                 } else {
                     if let Some(name) = section_name {
-                        crate::log::error::hook_error(
+                        tinct::log::error::hook_error(
                             name,
                             String::from_utf8_lossy(&result.stderr).as_ref(),
                         );
@@ -205,7 +227,7 @@ pub fn run_post_hook(
             }
             Err(e) => {
                 if let Some(name) = section_name {
-                    crate::log::error::hook_error(name, &e.to_string());
+                    tinct::log::error::hook_error(name, &e.to_string());
                 }
                 false
             }
@@ -229,6 +251,107 @@ fn is_executable(_path: &Path) -> bool {
     true
 }
 
+/// Read the config file, or `None` when there is no file at the path
+pub fn load_optional_config(config_path: &str) -> Result<Option<Config>, String> {
+    let content = match fs::read_to_string(config_path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(format!(
+                "Could not read config file '{}': {}",
+                config_path, e
+            ))
+        }
+    };
+    let config: Config = toml::from_str(&content).map_err(|e| {
+        format!(
+            "Invalid TOML format in config file '{}': {}",
+            config_path, e
+        )
+    })?;
+    config
+        .adjustments()
+        .validate()
+        .map_err(|e| format!("Invalid config file '{}': {}", config_path, e))?;
+    Ok(Some(config))
+}
+
+// Palette generation settings: command line arguments override the config file
+pub fn palette_options(args: &CliArgs, config: Option<&Config>) -> PaletteOptions {
+    // A source color alone needs a dynamic scheme to build the other colors
+    let default_scheme =
+        (args.image.is_some() || args.color.is_some()).then_some(scheme::Scheme::TonalSpot);
+    PaletteOptions {
+        scheme: args
+            .scheme
+            .or_else(|| config.and_then(|c| c.scheme))
+            .or(default_scheme),
+        contrast: args
+            .contrast
+            .or_else(|| config.and_then(|c| c.contrast))
+            .unwrap_or_default(),
+        metric: args
+            .contrast_metric
+            .or_else(|| config.and_then(|c| c.contrast_metric))
+            .unwrap_or_default(),
+        fix_disliked: config.and_then(|c| c.fix_disliked).unwrap_or(true),
+        adjustments: PaletteAdjustments {
+            hue_shift: args.hue_shift,
+            chroma_scale: args.chroma_scale,
+            tone_offset: args.tone_offset,
+        }
+        .or(config.map(Config::adjustments).unwrap_or_default()),
+    }
+}
+
+/// Write the palette of the selected mode to a base16 scheme file
+pub fn export_base16(
+    args: &CliArgs,
+    theme_all: &Theme,
+    options: &PaletteOptions,
+    output_path: &str,
+) -> Result<(), String> {
+    let (theme_mode, effective_mode) = theme_all.mode(&args.mode.to_string())?;
+    let is_dark_mode = effective_mode == "dark";
+    let palette = theme::generate_palette_with_options(&theme_mode, is_dark_mode, false, options)?;
+
+    // Named after the theme file, or the tool for themes built from a source color
+    let name = args
+        .theme
+        .as_deref()
+        .and_then(|theme| Path::new(theme).file_stem())
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("tinct");
+    let yaml = base16::to_base16_yaml(&palette, name, is_dark_mode)?;
+    theme::save_output(&yaml, output_path)
+}
+
+/// Write the palette of the selected mode to pywal cache files
+pub fn write_pywal_cache(
+    args: &CliArgs,
+    theme_all: &Theme,
+    options: &PaletteOptions,
+    cache_dir: &str,
+) -> Result<(), String> {
+    let (theme_mode, effective_mode) = theme_all.mode(&args.mode.to_string())?;
+    let is_dark_mode = effective_mode == "dark";
+    let palette = theme::generate_palette_with_options(&theme_mode, is_dark_mode, false, options)?;
+
+    // pywal records the wallpaper the colors came from, for tools that set it
+    let wallpaper = args
+        .image
+        .as_deref()
+        .map(|image| {
+            let image = shellexpand::tilde(image).to_string();
+            fs::canonicalize(&image)
+                .map(|path| path.to_string_lossy().to_string())
+                .unwrap_or(image)
+        })
+        .unwrap_or_default();
+    let cache_dir = shellexpand::tilde(cache_dir).to_string();
+    PywalColors::from_palette(&palette)?.write_cache(Path::new(&cache_dir), &wallpaper)
+}
+
 // Configuration validation
 pub fn validate_config_section(section: &ConfigSection, section_name: &str) -> bool {
     let mut is_valid = true;
@@ -250,8 +373,9 @@ pub fn validate_config_section(section: &ConfigSection, section_name: &str) -> b
 pub fn process_section(
     section_name: &str,
     section: &ConfigSection,
-    theme_file: &str,
+    theme_all: &Theme,
     mode: &str,
+    options: &PaletteOptions,
     _log_level: LogLevel,
 ) -> bool {
     let input_path = &section.input_path;
@@ -260,7 +384,7 @@ pub fn process_section(
 
     // Validate input file exists
     if !Path::new(input_path).exists() {
-        crate::log::error::message(
+        tinct::log::error::message(
             section_name,
             &format!("Input file '{}' does not exist. Skipping.", input_path),
         );
@@ -270,7 +394,7 @@ pub fn process_section(
     // Ensure output directory exists
     if let Some(parent) = Path::new(output_path).parent() {
         if let Err(e) = fs::create_dir_all(parent) {
-            crate::log::error::message(
+            tinct::log::error::message(
                 section_name,
                 &format!("Error creating output directory: {}. Skipping.", e),
             );
@@ -279,7 +403,7 @@ pub fn process_section(
     }
 
     // Process the theme
-    match theme::process_theme(theme_file, input_path, output_path, mode) {
+    match theme::process_theme_data(theme_all, input_path, output_path, mode, options) {
         Ok(()) => {
             // Run post hook if specified
             // The section is considered successful based on the post hook result
@@ -290,7 +414,7 @@ pub fn process_section(
             }
         }
         Err(e) => {
-            crate::log::error::theme_error(section_name, &e.to_string());
+            tinct::log::error::theme_error(section_name, &e.to_string());
             false
         }
     }
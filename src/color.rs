@@ -1,5 +1,35 @@
+mod apca;
+mod blend;
+mod cam16;
+mod cvd;
+mod dislike;
+mod gamut;
+mod hct;
+mod lab;
+mod oklab;
+mod parse;
+mod temperature;
+mod tonal_palette;
+mod xterm;
+
+pub use apca::*;
+pub use blend::*;
+pub use cam16::*;
+pub use cvd::*;
+pub use dislike::*;
+pub use gamut::*;
+pub use hct::*;
+pub use lab::*;
+pub use oklab::*;
+pub use parse::*;
+pub use temperature::*;
+pub use tonal_palette::*;
+pub use xterm::*;
+
+use crate::scheme::{self, ContrastLevel, ContrastMetric};
+
 // Color format representation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Rgb {
     pub r: u8,
     pub g: u8,
@@ -19,24 +49,29 @@ pub fn clamp(n: f64, minn: f64, maxn: f64) -> f64 {
     n.max(minn).min(maxn)
 }
 
-/// Convert HEX color to RGB values
+/// Convert a color to RGB values
+///
+/// Accepts HEX and every other CSS syntax `parse_color` does; alpha is dropped.
 pub fn hex_to_rgb(hex_color: &str) -> Result<Rgb, String> {
-    let hex = hex_color.trim_start_matches('#');
-    if hex.len() != 6 {
+    parse_color(hex_color).map(|rgba| rgba.rgb())
+}
+
+/// Parse a color that must be opaque, such as a background
+fn opaque_rgb(color: &str) -> Result<Rgb, String> {
+    let rgba = parse_color(color)?;
+    if rgba.a < 255 {
         return Err(format!(
-            "Invalid hex color format: {}. Expected 6 characters.",
-            hex
+            "Color '{}' is translucent; its appearance depends on what is behind it",
+            color
         ));
     }
+    Ok(rgba.rgb())
+}
 
-    let r = u8::from_str_radix(&hex[0..2], 16)
-        .map_err(|_| format!("Invalid hex color format: {}", hex))?;
-    let g = u8::from_str_radix(&hex[2..4], 16)
-        .map_err(|_| format!("Invalid hex color format: {}", hex))?;
-    let b = u8::from_str_radix(&hex[4..6], 16)
-        .map_err(|_| format!("Invalid hex color format: {}", hex))?;
-
-    Ok(Rgb { r, g, b })
+/// Parse a foreground color drawn over a background, blending in a translucent one
+fn rgb_over(foreground: &str, background: &str) -> Result<(Rgb, Rgb), String> {
+    let background = opaque_rgb(background)?;
+    Ok((parse_color(foreground)?.over(&background), background))
 }
 
 /// Convert RGB values to HEX color
@@ -142,10 +177,15 @@ pub fn hsl_to_rgb(h: f64, s: f64, l: f64) -> Rgb {
 }
 
 /// Calculate relative luminance of a color
+///
+/// Accepts any CSS syntax. Translucent colors are an error, as their
+/// luminance depends on what is behind them.
 #[allow(dead_code)]
 pub fn get_luminance(hexcolor: &str) -> Result<f64, String> {
-    let rgb = hex_to_rgb(hexcolor)?;
+    Ok(relative_luminance(&opaque_rgb(hexcolor)?))
+}
 
+fn relative_luminance(rgb: &Rgb) -> f64 {
     fn convert(v: u8) -> f64 {
         let v = v as f64 / 255.0;
         if v <= 0.03928 {
@@ -158,20 +198,84 @@ pub fn get_luminance(hexcolor: &str) -> Result<f64, String> {
     let r = convert(rgb.r);
     let g = convert(rgb.g);
     let b = convert(rgb.b);
-    Ok(0.2126 * r + 0.7152 * g + 0.0722 * b)
+    0.2126 * r + 0.7152 * g + 0.0722 * b
 }
 
 /// Calculate contrast ratio between two colors
+///
+/// `a` is drawn over `b`: a translucent `a` is blended over `b` first, and
+/// `b` must be opaque.
 #[allow(dead_code)]
 pub fn get_contrast_ratio(a: &str, b: &str) -> Result<f64, String> {
-    let l1 = get_luminance(a)?;
-    let l2 = get_luminance(b)?;
+    let (a, b) = rgb_over(a, b)?;
+    let l1 = relative_luminance(&a);
+    let l2 = relative_luminance(&b);
 
     let bright = l1.max(l2);
     let dark = l1.min(l2);
     Ok((bright + 0.05) / (dark + 0.05))
 }
 
+/// Calculate the APCA lightness contrast (Lc) of a text color on a background color
+///
+/// Translucent text is blended over the background, which must be opaque.
+pub fn get_apca_contrast(text: &str, background: &str) -> Result<f64, String> {
+    let (text, background) = rgb_over(text, background)?;
+    Ok(apca_contrast(&text, &background))
+}
+
+/// Calculate the CIEDE2000 color difference between two colors
+pub fn get_color_difference(a: &str, b: &str) -> Result<f64, String> {
+    let lab1 = Lab::from_rgb(&hex_to_rgb(a)?);
+    let lab2 = Lab::from_rgb(&hex_to_rgb(b)?);
+    Ok(delta_e_2000(&lab1, &lab2))
+}
+
+/// Calculate the WCAG contrast ratio between two tones (L* values)
+pub fn contrast_ratio_of_tones(a: f64, b: f64) -> f64 {
+    let y1 = y_from_lstar(clamp(a, 0.0, 100.0));
+    let y2 = y_from_lstar(clamp(b, 0.0, 100.0));
+    (y1.max(y2) + 5.0) / (y1.min(y2) + 5.0)
+}
+
+/// Find a tone lighter than `tone` with at least the given contrast ratio against it
+///
+/// Returns `None` if no such tone exists.
+pub fn lighter_tone(tone: f64, ratio: f64) -> Option<f64> {
+    if !(0.0..=100.0).contains(&tone) {
+        return None;
+    }
+    let dark_y = y_from_lstar(tone);
+    let light_y = ratio * (dark_y + 5.0) - 5.0;
+    let real_contrast = (light_y.max(dark_y) + 5.0) / (light_y.min(dark_y) + 5.0);
+    if real_contrast < ratio && (real_contrast - ratio).abs() > 0.04 {
+        return None;
+    }
+    // Nudge the result to make up for rounding when converting back to a color
+    let result = lstar_from_y(light_y) + 0.4;
+    (0.0..=100.0).contains(&result).then_some(result)
+}
+
+/// Find a tone darker than `tone` with at least the given contrast ratio against it
+///
+/// Returns `None` if no such tone exists.
+pub fn darker_tone(tone: f64, ratio: f64) -> Option<f64> {
+    if !(0.0..=100.0).contains(&tone) {
+        return None;
+    }
+    let light_y = y_from_lstar(tone);
+    let dark_y = (light_y + 5.0) / ratio - 5.0;
+    if dark_y < 0.0 {
+        return None;
+    }
+    let real_contrast = (light_y.max(dark_y) + 5.0) / (light_y.min(dark_y) + 5.0);
+    if real_contrast < ratio && (real_contrast - ratio).abs() > 0.04 {
+        return None;
+    }
+    let result = lstar_from_y(dark_y) - 0.4;
+    (0.0..=100.0).contains(&result).then_some(result)
+}
+
 /// Check if a color is light or dark based on luminance
 #[allow(dead_code)]
 pub fn is_light_color(hexcolor: &str) -> Result<bool, String> {
@@ -221,81 +325,120 @@ pub fn adjust_lightness_and_saturation(hexcolor: &str, la: f64, sa: f64) -> Resu
     ))
 }
 
-// HCT (Hue-Chroma-Tone) color space implementation for Material Design 3
-// Note: This is a simplified implementation based on the concepts
-// A full HCT implementation would require CAM16 color space conversion
+/// Adjust the OKLCH lightness of a color by a given amount (in percentage points)
+pub fn adjust_oklch_lightness(hexcolor: &str, amount: f64) -> Result<String, String> {
+    adjust_oklch(hexcolor, amount, 0.0)
+}
 
-/// Represents a color in the HCT (Hue-Chroma-Tone) color space
-#[derive(Debug, Clone)]
-pub struct Hct {
-    pub h: f64, // Hue (0-360)
-    pub c: f64, // Chroma (0-100+)
-    pub t: f64, // Tone (0-100, equivalent to L* in L*a*b*)
-}
-
-impl Hct {
-    /// Create an HCT color from hue, chroma, and tone values
-    pub fn from_hct(h: f64, c: f64, t: f64) -> Self {
-        Self {
-            h: clamp(h, 0.0, 360.0),
-            c: clamp(c, 0.0, 200.0), // Chroma can go higher than 100
-            t: clamp(t, 0.0, 100.0),
-        }
-    }
+/// Adjust the OKLCH chroma of a color by a given amount
+pub fn adjust_oklch_chroma(hexcolor: &str, amount: f64) -> Result<String, String> {
+    adjust_oklch(hexcolor, 0.0, amount)
+}
 
-    /// Convert HCT to RGB
-    pub fn to_rgb(&self) -> Rgb {
-        // This is a simplified conversion
-        // A full implementation would require CAM16 conversion
-        // For now, we'll convert HCT to HSL then to RGB as an approximation
-        // Using a more accurate mapping from chroma to saturation
-        let hsl = Hsl {
-            h: self.h,
-            s: clamp(self.c * 0.8, 0.0, 100.0), // Better mapping from chroma to saturation
-            l: self.t,
-        };
-        hsl_to_rgb(hsl.h, hsl.s, hsl.l)
+/// Adjust both OKLCH lightness (in percentage points) and chroma of a color, keeping its hue
+pub fn adjust_oklch(hexcolor: &str, la: f64, ca: f64) -> Result<String, String> {
+    let rgb = hex_to_rgb(hexcolor)?;
+    let oklch = Oklch::from_rgb(&rgb);
+    let new_rgb = Oklch {
+        l: clamp(oklch.l + la / 100.0, 0.0, 1.0),
+        c: (oklch.c + ca).max(0.0),
+        h: oklch.h,
     }
+    .to_rgb();
+    Ok(rgb_to_hex(
+        new_rgb.r as f64,
+        new_rgb.g as f64,
+        new_rgb.b as f64,
+    ))
+}
 
-    /// Convert HCT to HEX
-    pub fn to_hex(&self) -> String {
-        let rgb = self.to_rgb();
-        rgb_to_hex(rgb.r as f64, rgb.g as f64, rgb.b as f64)
+// Color science helpers shared by the CAM16 / HCT implementation
+
+/// Normalize an angle in degrees to the range [0, 360)
+pub fn sanitize_degrees(degrees: f64) -> f64 {
+    let degrees = degrees % 360.0;
+    if degrees < 0.0 {
+        degrees + 360.0
+    } else {
+        degrees
     }
 }
 
-/// Generate HCT color from RGB
-pub fn rgb_to_hct(r: u8, g: u8, b: u8) -> Hct {
-    // This is a simplified conversion
-    // A full implementation would require CAM16 conversion
-    let hsl = rgb_to_hsl(r as f64, g as f64, b as f64);
-    Hct {
-        h: hsl.h,
-        c: clamp(hsl.s * 1.2, 0.0, 150.0), // Increase the chroma to compensate for the conversion loss
-        t: hsl.l,
+/// Shortest angular distance between two hues in degrees, from 0 to 180
+pub fn difference_degrees(a: f64, b: f64) -> f64 {
+    180.0 - ((a - b).abs() - 180.0).abs()
+}
+
+/// Sign of the shortest rotation from one hue to another: 1.0 or -1.0
+pub fn rotation_direction(from: f64, to: f64) -> f64 {
+    if sanitize_degrees(to - from) <= 180.0 {
+        1.0
+    } else {
+        -1.0
     }
 }
 
-/// Generate appropriate text color for a given background
-#[allow(dead_code)]
-pub fn generate_on_color(base: &str, _is_dark: bool) -> Result<String, String> {
-    // The _is_dark parameter determines the overall theme preference,
-    // but we still want to ensure good contrast regardless of the theme preference
-    let light = is_light_color(base)?;
-
-    if light {
-        // If the base color is light, we want dark text for contrast
-        if get_contrast_ratio(base, "#000000")? >= 4.5 {
-            Ok("#000000".to_string())
-        } else {
-            Ok("#1c1b1f".to_string())
-        }
+/// Convert an 8-bit sRGB channel to linear RGB in the range 0-100
+pub fn linearized(component: u8) -> f64 {
+    let normalized = component as f64 / 255.0;
+    if normalized <= 0.040449936 {
+        normalized / 12.92 * 100.0
     } else {
-        // If the base color is dark, we want light text for contrast
-        if get_contrast_ratio(base, "#ffffff")? >= 4.5 {
-            Ok("#ffffff".to_string())
-        } else {
-            Ok("#e6e1e5".to_string())
-        }
+        ((normalized + 0.055) / 1.055).powf(2.4) * 100.0
     }
 }
+
+/// Convert a linear RGB channel in the range 0-100 to an 8-bit sRGB channel
+pub fn delinearized(component: f64) -> u8 {
+    let normalized = component / 100.0;
+    let delinearized = if normalized <= 0.0031308 {
+        normalized * 12.92
+    } else {
+        1.055 * normalized.powf(1.0 / 2.4) - 0.055
+    };
+    clamp((delinearized * 255.0).round(), 0.0, 255.0) as u8
+}
+
+/// Convert L* (0-100) to relative luminance Y (0-100)
+pub fn y_from_lstar(lstar: f64) -> f64 {
+    let ft = (lstar + 16.0) / 116.0;
+    let ft3 = ft * ft * ft;
+    let e = 216.0 / 24389.0;
+    let kappa = 24389.0 / 27.0;
+    let normalized = if ft3 > e {
+        ft3
+    } else {
+        (116.0 * ft - 16.0) / kappa
+    };
+    normalized * 100.0
+}
+
+/// Convert relative luminance Y (0-100) to L* (0-100)
+pub fn lstar_from_y(y: f64) -> f64 {
+    let t = y / 100.0;
+    let e = 216.0 / 24389.0;
+    let kappa = 24389.0 / 27.0;
+    let f = if t > e {
+        t.cbrt()
+    } else {
+        (kappa * t + 16.0) / 116.0
+    };
+    116.0 * f - 16.0
+}
+
+/// Generate a text color for a background, in the background's hue family
+///
+/// The tone is the one closest to the background that meets the on-color
+/// contrast target at `level` (7:1 at standard contrast), measured with
+/// `metric`; if no tone gets there, the one with the most contrast is used.
+pub fn generate_on_color(
+    base: &str,
+    level: ContrastLevel,
+    metric: ContrastMetric,
+) -> Result<String, String> {
+    let rgb = hex_to_rgb(base)?;
+    let hct = rgb_to_hct(rgb.r, rgb.g, rgb.b);
+    let palette = TonalPalette::from_hue_and_chroma(hct.h, hct.c);
+    let target = metric.target(scheme::CURVE_ON_COLOR.get(level));
+    Ok(palette.hex(scheme::foreground_tone(hct.t, target, metric)))
+}
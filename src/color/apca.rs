@@ -0,0 +1,82 @@
+// APCA (Accessible Perceptual Contrast Algorithm), version 0.0.98G-4g
+// Lightness contrast Lc ranges from about -108 to 106; positive values mean
+// dark text on a light background, negative values light text on a dark one
+
+use super::{y_from_lstar, Rgb};
+
+const MAIN_TRC: f64 = 2.4;
+const S_R_CO: f64 = 0.2126729;
+const S_G_CO: f64 = 0.7151522;
+const S_B_CO: f64 = 0.0721750;
+
+const NORM_BG: f64 = 0.56;
+const NORM_TXT: f64 = 0.57;
+const REV_TXT: f64 = 0.62;
+const REV_BG: f64 = 0.65;
+
+const BLK_THRS: f64 = 0.022;
+const BLK_CLMP: f64 = 1.414;
+const SCALE_BOW: f64 = 1.14;
+const SCALE_WOB: f64 = 1.14;
+const LO_BOW_OFFSET: f64 = 0.027;
+const LO_WOB_OFFSET: f64 = 0.027;
+const DELTA_Y_MIN: f64 = 0.0005;
+const LO_CLIP: f64 = 0.1;
+
+/// APCA screen luminance of an sRGB color
+pub fn apca_luminance(rgb: &Rgb) -> f64 {
+    let channel = |c: u8| (c as f64 / 255.0).powf(MAIN_TRC);
+    S_R_CO * channel(rgb.r) + S_G_CO * channel(rgb.g) + S_B_CO * channel(rgb.b)
+}
+
+/// APCA screen luminance of a gray with the given tone (L*)
+pub fn apca_luminance_of_tone(tone: f64) -> f64 {
+    let y = y_from_lstar(tone) / 100.0;
+    let encoded = if y <= 0.0031308 {
+        y * 12.92
+    } else {
+        1.055 * y.powf(1.0 / 2.4) - 0.055
+    };
+    encoded.max(0.0).powf(MAIN_TRC)
+}
+
+/// APCA lightness contrast (Lc) between text and background screen luminances
+pub fn apca_contrast_of_luminances(text_y: f64, background_y: f64) -> f64 {
+    let soft_clamp = |y: f64| {
+        if y > BLK_THRS {
+            y
+        } else {
+            y + (BLK_THRS - y).powf(BLK_CLMP)
+        }
+    };
+    let text_y = soft_clamp(text_y);
+    let background_y = soft_clamp(background_y);
+
+    if (background_y - text_y).abs() < DELTA_Y_MIN {
+        return 0.0;
+    }
+
+    let output = if background_y > text_y {
+        // Dark text on a light background
+        let sapc = (background_y.powf(NORM_BG) - text_y.powf(NORM_TXT)) * SCALE_BOW;
+        if sapc < LO_CLIP {
+            0.0
+        } else {
+            sapc - LO_BOW_OFFSET
+        }
+    } else {
+        // Light text on a dark background
+        let sapc = (background_y.powf(REV_BG) - text_y.powf(REV_TXT)) * SCALE_WOB;
+        if sapc > -LO_CLIP {
+            0.0
+        } else {
+            sapc + LO_WOB_OFFSET
+        }
+    };
+    output * 100.0
+}
+
+/// APCA lightness contrast (Lc) of text drawn on a background
+pub fn apca_contrast(text: &Rgb, background: &Rgb) -> f64 {
+    apca_contrast_of_luminances(apca_luminance(text), apca_luminance(background))
+}
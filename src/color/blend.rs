@@ -0,0 +1,90 @@
+// Blending and mixing colors
+// The CAM16-UCS and HCT hue blends are ported from material-color-utilities' Blend
+
+use super::{
+    delinearized, difference_degrees, linearized, rgb_to_hct, rotation_direction, sanitize_degrees,
+    Cam16, Hct, Oklab, Rgb,
+};
+
+/// Linear interpolation, with `amount` clamped to 0.0-1.0
+fn lerp(from: f64, to: f64, amount: f64) -> f64 {
+    from + (to - from) * amount.clamp(0.0, 1.0)
+}
+
+/// Mix two colors in linear sRGB, i.e. the physical mix of their light
+///
+/// `amount` goes from 0.0 (`from`) to 1.0 (`to`).
+pub fn mix_linear_srgb(from: &Rgb, to: &Rgb, amount: f64) -> Rgb {
+    let mix = |a: u8, b: u8| delinearized(lerp(linearized(a), linearized(b), amount));
+    Rgb {
+        r: mix(from.r, to.r),
+        g: mix(from.g, to.g),
+        b: mix(from.b, to.b),
+    }
+}
+
+/// Mix two colors in OKLab, so lightness changes evenly along the way
+///
+/// `amount` goes from 0.0 (`from`) to 1.0 (`to`).
+pub fn mix_oklab(from: &Rgb, to: &Rgb, amount: f64) -> Rgb {
+    let (from, to) = (Oklab::from_rgb(from), Oklab::from_rgb(to));
+    Oklab {
+        l: lerp(from.l, to.l, amount),
+        a: lerp(from.a, to.a, amount),
+        b: lerp(from.b, to.b, amount),
+    }
+    .to_rgb()
+}
+
+/// Blend two colors in CAM16-UCS, interpolating lightness, hue and chroma together
+///
+/// `amount` goes from 0.0 (`from`) to 1.0 (`to`).
+pub fn blend_cam16_ucs(from: &Rgb, to: &Rgb, amount: f64) -> Rgb {
+    let (from, to) = (Cam16::from_rgb(from), Cam16::from_rgb(to));
+    Cam16::from_ucs(
+        lerp(from.jstar, to.jstar, amount),
+        lerp(from.astar, to.astar, amount),
+        lerp(from.bstar, to.bstar, amount),
+    )
+    .to_rgb()
+}
+
+/// Blend only the hue of a color toward another, keeping its chroma and tone
+///
+/// The hue is taken from the CAM16-UCS blend of the two colors, so it moves
+/// the way it would when mixing them. `amount` goes from 0.0 to 1.0.
+pub fn blend_hct_hue(from: &Rgb, to: &Rgb, amount: f64) -> Rgb {
+    let blended = blend_cam16_ucs(from, to, amount);
+    let blended_hue = rgb_to_hct(blended.r, blended.g, blended.b).h;
+    let from = rgb_to_hct(from.r, from.g, from.b);
+    Hct::from_hct(blended_hue, from.c, from.t).to_rgb()
+}
+
+/// Mix a color with white in OKLab, from 0.0 (unchanged) to 1.0 (white)
+pub fn tint(color: &Rgb, amount: f64) -> Rgb {
+    mix_oklab(
+        color,
+        &Rgb {
+            r: 255,
+            g: 255,
+            b: 255,
+        },
+        amount,
+    )
+}
+
+/// Mix a color with black in OKLab, from 0.0 (unchanged) to 1.0 (black)
+pub fn shade(color: &Rgb, amount: f64) -> Rgb {
+    mix_oklab(color, &Rgb { r: 0, g: 0, b: 0 }, amount)
+}
+
+/// Rotate the hue of a design color toward a source color, keeping its chroma and tone
+///
+/// The hue moves halfway toward the source hue, by at most 15 degrees, so
+/// semantic colors (e.g. a green for success) fit the theme while staying
+/// recognizable.
+pub fn harmonize(design: &Hct, source: &Hct) -> Hct {
+    let rotation = (difference_degrees(design.h, source.h) * 0.5).min(15.0);
+    let hue = sanitize_degrees(design.h + rotation * rotation_direction(design.h, source.h));
+    Hct::from_hct(hue, design.c, design.t)
+}
@@ -0,0 +1,363 @@
+// CAM16 color appearance model
+// Based on the reference implementation used by Material Design 3 (material-color-utilities)
+
+use super::{clamp, delinearized, linearized, sanitize_degrees, y_from_lstar, Rgb};
+use std::f64::consts::PI;
+use std::sync::OnceLock;
+
+/// sRGB (linear, 0-100) to XYZ (D65) matrix
+pub(crate) const SRGB_TO_XYZ: [[f64; 3]; 3] = [
+    [0.41233895, 0.35762064, 0.18051042],
+    [0.2126, 0.7152, 0.0722],
+    [0.01932141, 0.11916382, 0.95034478],
+];
+
+/// XYZ (D65) to sRGB (linear, 0-100) matrix
+pub(crate) const XYZ_TO_SRGB: [[f64; 3]; 3] = [
+    [
+        3.2413774792388685,
+        -1.5376652402851851,
+        -0.49885366846268053,
+    ],
+    [-0.9691452513005321, 1.8758853451067872, 0.04156585616912061],
+    [
+        0.05562093689691305,
+        -0.20395524564742123,
+        1.0571799111220335,
+    ],
+];
+
+/// XYZ to CAM16 cone response matrix
+pub(crate) const XYZ_TO_CAM16RGB: [[f64; 3]; 3] = [
+    [0.401288, 0.650173, -0.051461],
+    [-0.250268, 1.204414, 0.045854],
+    [-0.002079, 0.048952, 0.953127],
+];
+
+/// CAM16 cone response to XYZ matrix
+pub(crate) const CAM16RGB_TO_XYZ: [[f64; 3]; 3] = [
+    [1.8620678, -1.0112547, 0.14918678],
+    [0.38752654, 0.62144744, -0.00897398],
+    [-0.0158415, -0.03412294, 1.0499644],
+];
+
+/// D65 white point in XYZ
+pub const WHITE_POINT_D65: [f64; 3] = [95.047, 100.0, 108.883];
+
+pub(crate) fn matrix_multiply(row: [f64; 3], matrix: &[[f64; 3]; 3]) -> [f64; 3] {
+    [
+        row[0] * matrix[0][0] + row[1] * matrix[0][1] + row[2] * matrix[0][2],
+        row[0] * matrix[1][0] + row[1] * matrix[1][1] + row[2] * matrix[1][2],
+        row[0] * matrix[2][0] + row[1] * matrix[2][1] + row[2] * matrix[2][2],
+    ]
+}
+
+fn lerp(start: f64, stop: f64, amount: f64) -> f64 {
+    (1.0 - amount) * start + amount * stop
+}
+
+/// Convert an sRGB color to XYZ (D65, Y in 0-100)
+pub fn rgb_to_xyz(rgb: &Rgb) -> [f64; 3] {
+    let linear = [linearized(rgb.r), linearized(rgb.g), linearized(rgb.b)];
+    matrix_multiply(linear, &SRGB_TO_XYZ)
+}
+
+/// Convert XYZ (D65, Y in 0-100) to an 8-bit sRGB color, clamping each channel
+pub fn xyz_to_rgb(x: f64, y: f64, z: f64) -> Rgb {
+    let linear = matrix_multiply([x, y, z], &XYZ_TO_SRGB);
+    Rgb {
+        r: delinearized(linear[0]),
+        g: delinearized(linear[1]),
+        b: delinearized(linear[2]),
+    }
+}
+
+/// The environment a color is viewed in, used by CAM16 to model perception
+#[derive(Debug, Clone)]
+pub struct ViewingConditions {
+    pub n: f64,
+    pub aw: f64,
+    pub nbb: f64,
+    pub ncb: f64,
+    pub c: f64,
+    pub nc: f64,
+    pub rgb_d: [f64; 3],
+    pub fl: f64,
+    pub fl_root: f64,
+    pub z: f64,
+}
+
+impl ViewingConditions {
+    /// Create viewing conditions from the physical parameters of the environment
+    ///
+    /// `adapting_luminance` is in cd/m², `background_lstar` is the L* of the
+    /// background and `surround` ranges from 0 (dark) to 2 (average).
+    pub fn make(
+        white_point: [f64; 3],
+        adapting_luminance: f64,
+        background_lstar: f64,
+        surround: f64,
+        discounting_illuminant: bool,
+    ) -> Self {
+        let background_lstar = background_lstar.max(0.1);
+        let rgb_w = matrix_multiply(white_point, &XYZ_TO_CAM16RGB);
+
+        let f = 0.8 + surround / 10.0;
+        let c = if f >= 0.9 {
+            lerp(0.59, 0.69, (f - 0.9) * 10.0)
+        } else {
+            lerp(0.525, 0.59, (f - 0.8) * 10.0)
+        };
+        let d = if discounting_illuminant {
+            1.0
+        } else {
+            f * (1.0 - (1.0 / 3.6) * ((-adapting_luminance - 42.0) / 92.0).exp())
+        };
+        let d = clamp(d, 0.0, 1.0);
+        let nc = f;
+        let rgb_d = [
+            d * (100.0 / rgb_w[0]) + 1.0 - d,
+            d * (100.0 / rgb_w[1]) + 1.0 - d,
+            d * (100.0 / rgb_w[2]) + 1.0 - d,
+        ];
+
+        let k = 1.0 / (5.0 * adapting_luminance + 1.0);
+        let k4 = k * k * k * k;
+        let k4f = 1.0 - k4;
+        let fl = k4 * adapting_luminance + 0.1 * k4f * k4f * (5.0 * adapting_luminance).cbrt();
+        let n = y_from_lstar(background_lstar) / white_point[1];
+        let z = 1.48 + n.sqrt();
+        let nbb = 0.725 / n.powf(0.2);
+        let ncb = nbb;
+
+        let rgb_a_factors = [
+            (fl * rgb_d[0] * rgb_w[0] / 100.0).powf(0.42),
+            (fl * rgb_d[1] * rgb_w[1] / 100.0).powf(0.42),
+            (fl * rgb_d[2] * rgb_w[2] / 100.0).powf(0.42),
+        ];
+        let rgb_a = rgb_a_factors.map(|f| 400.0 * f / (f + 27.13));
+        let aw = (2.0 * rgb_a[0] + rgb_a[1] + 0.05 * rgb_a[2]) * nbb;
+
+        Self {
+            n,
+            aw,
+            nbb,
+            ncb,
+            c,
+            nc,
+            rgb_d,
+            fl,
+            fl_root: fl.powf(0.25),
+            z,
+        }
+    }
+
+    /// Standard sRGB-like viewing conditions shared by all HCT calculations
+    pub fn standard() -> &'static ViewingConditions {
+        static STANDARD: OnceLock<ViewingConditions> = OnceLock::new();
+        STANDARD.get_or_init(ViewingConditions::default)
+    }
+}
+
+impl Default for ViewingConditions {
+    fn default() -> Self {
+        Self::make(
+            WHITE_POINT_D65,
+            (200.0 / PI) * y_from_lstar(50.0) / 100.0,
+            50.0,
+            2.0,
+            false,
+        )
+    }
+}
+
+/// A color in the CAM16 color appearance model, along with its CAM16-UCS coordinates
+#[derive(Debug, Clone)]
+pub struct Cam16 {
+    pub hue: f64,
+    pub chroma: f64,
+    pub j: f64,
+    pub q: f64,
+    pub m: f64,
+    pub s: f64,
+    pub jstar: f64,
+    pub astar: f64,
+    pub bstar: f64,
+}
+
+impl Cam16 {
+    /// Create a CAM16 color from an sRGB color in standard viewing conditions
+    pub fn from_rgb(rgb: &Rgb) -> Self {
+        Self::from_rgb_in_viewing_conditions(rgb, ViewingConditions::standard())
+    }
+
+    /// Create a CAM16 color from an sRGB color in the given viewing conditions
+    pub fn from_rgb_in_viewing_conditions(rgb: &Rgb, vc: &ViewingConditions) -> Self {
+        let xyz = rgb_to_xyz(rgb);
+        Self::from_xyz_in_viewing_conditions(xyz[0], xyz[1], xyz[2], vc)
+    }
+
+    /// Create a CAM16 color from XYZ coordinates in the given viewing conditions
+    pub fn from_xyz_in_viewing_conditions(x: f64, y: f64, z: f64, vc: &ViewingConditions) -> Self {
+        let rgb_c = matrix_multiply([x, y, z], &XYZ_TO_CAM16RGB);
+        let rgb_d = [
+            vc.rgb_d[0] * rgb_c[0],
+            vc.rgb_d[1] * rgb_c[1],
+            vc.rgb_d[2] * rgb_c[2],
+        ];
+        let rgb_a = rgb_d.map(|component| {
+            let af = (vc.fl * component.abs() / 100.0).powf(0.42);
+            component.signum() * 400.0 * af / (af + 27.13)
+        });
+        let [r_a, g_a, b_a] = rgb_a;
+
+        let a = (11.0 * r_a + -12.0 * g_a + b_a) / 11.0;
+        let b = (r_a + g_a - 2.0 * b_a) / 9.0;
+        let u = (20.0 * r_a + 20.0 * g_a + 21.0 * b_a) / 20.0;
+        let p2 = (40.0 * r_a + 20.0 * g_a + b_a) / 20.0;
+
+        let hue = sanitize_degrees(b.atan2(a).to_degrees());
+        let hue_radians = hue.to_radians();
+
+        let ac = p2 * vc.nbb;
+        let j = 100.0 * (ac / vc.aw).powf(vc.c * vc.z);
+        let q = (4.0 / vc.c) * (j / 100.0).sqrt() * (vc.aw + 4.0) * vc.fl_root;
+
+        let hue_prime = if hue < 20.14 { hue + 360.0 } else { hue };
+        let e_hue = 0.25 * ((hue_prime.to_radians() + 2.0).cos() + 3.8);
+        let p1 = 50000.0 / 13.0 * e_hue * vc.nc * vc.ncb;
+        let t = p1 * a.hypot(b) / (u + 0.305);
+        let alpha = t.powf(0.9) * (1.64 - 0.29f64.powf(vc.n)).powf(0.73);
+        let chroma = alpha * (j / 100.0).sqrt();
+        let m = chroma * vc.fl_root;
+        let s = 50.0 * ((alpha * vc.c) / (vc.aw + 4.0)).sqrt();
+
+        let jstar = (1.0 + 100.0 * 0.007) * j / (1.0 + 0.007 * j);
+        let mstar = (1.0 / 0.0228) * (1.0 + 0.0228 * m).ln();
+
+        Self {
+            hue,
+            chroma,
+            j,
+            q,
+            m,
+            s,
+            jstar,
+            astar: mstar * hue_radians.cos(),
+            bstar: mstar * hue_radians.sin(),
+        }
+    }
+
+    /// Create a CAM16 color from lightness (J), chroma and hue in standard viewing conditions
+    pub fn from_jch(j: f64, chroma: f64, hue: f64) -> Self {
+        Self::from_jch_in_viewing_conditions(j, chroma, hue, ViewingConditions::standard())
+    }
+
+    /// Create a CAM16 color from lightness (J), chroma and hue in the given viewing conditions
+    pub fn from_jch_in_viewing_conditions(
+        j: f64,
+        chroma: f64,
+        hue: f64,
+        vc: &ViewingConditions,
+    ) -> Self {
+        let q = (4.0 / vc.c) * (j / 100.0).sqrt() * (vc.aw + 4.0) * vc.fl_root;
+        let m = chroma * vc.fl_root;
+        let alpha = chroma / (j / 100.0).sqrt();
+        let s = 50.0 * ((alpha * vc.c) / (vc.aw + 4.0)).sqrt();
+
+        let hue_radians = hue.to_radians();
+        let jstar = (1.0 + 100.0 * 0.007) * j / (1.0 + 0.007 * j);
+        let mstar = (1.0 / 0.0228) * (1.0 + 0.0228 * m).ln();
+
+        Self {
+            hue,
+            chroma,
+            j,
+            q,
+            m,
+            s,
+            jstar,
+            astar: mstar * hue_radians.cos(),
+            bstar: mstar * hue_radians.sin(),
+        }
+    }
+
+    /// Create a CAM16 color from CAM16-UCS coordinates in standard viewing conditions
+    pub fn from_ucs(jstar: f64, astar: f64, bstar: f64) -> Self {
+        Self::from_ucs_in_viewing_conditions(jstar, astar, bstar, ViewingConditions::standard())
+    }
+
+    /// Create a CAM16 color from CAM16-UCS coordinates in the given viewing conditions
+    pub fn from_ucs_in_viewing_conditions(
+        jstar: f64,
+        astar: f64,
+        bstar: f64,
+        vc: &ViewingConditions,
+    ) -> Self {
+        let m = astar.hypot(bstar);
+        let m2 = ((m * 0.0228).exp() - 1.0) / 0.0228;
+        let chroma = m2 / vc.fl_root;
+        let hue = sanitize_degrees(bstar.atan2(astar).to_degrees());
+        let j = jstar / (1.0 - (jstar - 100.0) * 0.007);
+        Self::from_jch_in_viewing_conditions(j, chroma, hue, vc)
+    }
+
+    /// Euclidean distance between two colors in CAM16-UCS
+    pub fn distance(&self, other: &Cam16) -> f64 {
+        let d_j = self.jstar - other.jstar;
+        let d_a = self.astar - other.astar;
+        let d_b = self.bstar - other.bstar;
+        let d_e_prime = (d_j * d_j + d_a * d_a + d_b * d_b).sqrt();
+        1.41 * d_e_prime.powf(0.63)
+    }
+
+    /// Convert this color to sRGB in standard viewing conditions
+    pub fn to_rgb(&self) -> Rgb {
+        self.viewed(ViewingConditions::standard())
+    }
+
+    /// Convert this color to XYZ in the given viewing conditions
+    pub fn xyz_in_viewing_conditions(&self, vc: &ViewingConditions) -> [f64; 3] {
+        let alpha = if self.chroma == 0.0 || self.j == 0.0 {
+            0.0
+        } else {
+            self.chroma / (self.j / 100.0).sqrt()
+        };
+
+        let t = (alpha / (1.64 - 0.29f64.powf(vc.n)).powf(0.73)).powf(1.0 / 0.9);
+        let h_rad = self.hue.to_radians();
+
+        let e_hue = 0.25 * ((h_rad + 2.0).cos() + 3.8);
+        let ac = vc.aw * (self.j / 100.0).powf(1.0 / vc.c / vc.z);
+        let p1 = e_hue * (50000.0 / 13.0) * vc.nc * vc.ncb;
+        let p2 = ac / vc.nbb;
+
+        let h_sin = h_rad.sin();
+        let h_cos = h_rad.cos();
+
+        let gamma = (23.0 * (p2 + 0.305) * t) / (23.0 * p1 + 11.0 * t * h_cos + 108.0 * t * h_sin);
+        let a = gamma * h_cos;
+        let b = gamma * h_sin;
+        let r_a = (460.0 * p2 + 451.0 * a + 288.0 * b) / 1403.0;
+        let g_a = (460.0 * p2 - 891.0 * a - 261.0 * b) / 1403.0;
+        let b_a = (460.0 * p2 - 220.0 * a - 6300.0 * b) / 1403.0;
+
+        let rgb_c = [r_a, g_a, b_a].map(|adapted| {
+            let base = (27.13 * adapted.abs() / (400.0 - adapted.abs())).max(0.0);
+            adapted.signum() * (100.0 / vc.fl) * base.powf(1.0 / 0.42)
+        });
+        let rgb_f = [
+            rgb_c[0] / vc.rgb_d[0],
+            rgb_c[1] / vc.rgb_d[1],
+            rgb_c[2] / vc.rgb_d[2],
+        ];
+
+        matrix_multiply(rgb_f, &CAM16RGB_TO_XYZ)
+    }
+
+    /// Convert this color to sRGB in the given viewing conditions
+    pub fn viewed(&self, vc: &ViewingConditions) -> Rgb {
+        let xyz = self.xyz_in_viewing_conditions(vc);
+        xyz_to_rgb(xyz[0], xyz[1], xyz[2])
+    }
+}
@@ -0,0 +1,23 @@
+// Detection and correction of universally disliked colors
+// Ported from material-color-utilities' DislikeAnalyzer: dark yellow-greens
+// are consistently rated unpleasant and read as bile, mold or swamp, while
+// lighter colors of the same hue are fine
+
+use super::Hct;
+
+/// Whether a color is a dark yellow-green: hue 90-111, chroma above 16 and tone below 65
+pub fn is_disliked(hct: &Hct) -> bool {
+    let hue_passes = (90.0..=111.0).contains(&hct.h.round());
+    let chroma_passes = hct.c.round() > 16.0;
+    let tone_passes = hct.t.round() < 65.0;
+    hue_passes && chroma_passes && tone_passes
+}
+
+/// Lift a disliked color to tone 70, keeping its hue and chroma; other colors are returned as is
+pub fn fix_if_disliked(hct: &Hct) -> Hct {
+    if is_disliked(hct) {
+        Hct::from_hct(hct.h, hct.c, 70.0)
+    } else {
+        hct.clone()
+    }
+}
@@ -0,0 +1,247 @@
+// RGB gamuts (sRGB and Display P3) and CSS Color 4 gamut mapping
+// Out of gamut colors are brought in by reducing OKLCH chroma at constant
+// lightness and hue, rather than clamping each channel
+
+use super::cam16::{matrix_multiply, SRGB_TO_XYZ, XYZ_TO_SRGB};
+use super::{Oklab, Oklch, Rgb};
+
+/// Linear Display P3 to XYZ (D65, Y in 0-100) matrix
+const P3_TO_XYZ: [[f64; 3]; 3] = [
+    [48.65709486482162, 26.566769316909306, 19.82172852343625],
+    [22.89745640697488, 69.17385218365064, 7.9286914093745],
+    [0.0, 4.511338185890264, 104.3944368900976],
+];
+
+/// XYZ (D65, Y in 0-100) to linear Display P3 matrix
+const XYZ_TO_P3: [[f64; 3]; 3] = [
+    [
+        0.02493496911941425,
+        -0.009313836179191239,
+        -0.0040271078445071684,
+    ],
+    [
+        -0.008294889695615747,
+        0.017626640603183463,
+        0.00023624685841943577,
+    ],
+    [
+        0.0003584583024378447,
+        -0.0007617238926804182,
+        0.009568845240076872,
+    ],
+];
+
+/// Just noticeable difference in OKLab, below which clipping is acceptable
+const JND: f64 = 0.02;
+/// Chroma precision of the gamut mapping search
+const CHROMA_EPSILON: f64 = 0.0001;
+
+/// An RGB color gamut with the sRGB transfer function
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gamut {
+    Srgb,
+    DisplayP3,
+}
+
+impl Gamut {
+    /// Linear components (0.0-1.0 when in gamut) of an XYZ color
+    fn linear_from_xyz(&self, xyz: [f64; 3]) -> [f64; 3] {
+        match self {
+            Gamut::Srgb => matrix_multiply(xyz, &XYZ_TO_SRGB).map(|c| c / 100.0),
+            Gamut::DisplayP3 => matrix_multiply(xyz, &XYZ_TO_P3),
+        }
+    }
+
+    /// XYZ color of linear components
+    fn linear_to_xyz(&self, linear: [f64; 3]) -> [f64; 3] {
+        match self {
+            Gamut::Srgb => matrix_multiply(linear.map(|c| c * 100.0), &SRGB_TO_XYZ),
+            Gamut::DisplayP3 => matrix_multiply(linear, &P3_TO_XYZ),
+        }
+    }
+
+    /// Gamma-encoded components (0.0-1.0 when in gamut) of an OKLCH color
+    pub fn from_oklch(&self, color: &Oklch) -> [f64; 3] {
+        let linear_srgb = color.to_oklab().to_linear_srgb();
+        let xyz = Gamut::Srgb.linear_to_xyz(linear_srgb);
+        self.linear_from_xyz(xyz).map(encode)
+    }
+
+    /// OKLCH color of gamma-encoded components
+    pub fn to_oklch(&self, components: [f64; 3]) -> Oklch {
+        let xyz = self.linear_to_xyz(components.map(decode));
+        Oklab::from_linear_srgb(Gamut::Srgb.linear_from_xyz(xyz)).to_oklch()
+    }
+
+    pub fn contains(&self, color: &Oklch) -> bool {
+        const EPSILON: f64 = 1e-6;
+        self.from_oklch(color)
+            .iter()
+            .all(|c| (-EPSILON..=1.0 + EPSILON).contains(c))
+    }
+
+    /// Bring a color into this gamut, keeping its lightness and hue
+    ///
+    /// Implements the CSS Color 4 gamut mapping algorithm: chroma is reduced
+    /// until clipping the color changes it by less than a just noticeable
+    /// difference. Returns gamma-encoded components from 0.0 to 1.0.
+    pub fn map(&self, color: &Oklch) -> [f64; 3] {
+        let clip = |components: [f64; 3]| components.map(|c| c.clamp(0.0, 1.0));
+
+        if color.l >= 1.0 {
+            return [1.0; 3];
+        }
+        if color.l <= 0.0 {
+            return [0.0; 3];
+        }
+        if self.contains(color) {
+            return clip(self.from_oklch(color));
+        }
+
+        let mut current = *color;
+        let mut clipped = clip(self.from_oklch(&current));
+        if delta_e_ok(&self.to_oklch(clipped), &current) < JND {
+            return clipped;
+        }
+
+        let (mut min, mut max) = (0.0, color.c);
+        let mut min_in_gamut = true;
+        while max - min > CHROMA_EPSILON {
+            let chroma = (min + max) / 2.0;
+            current.c = chroma;
+            if min_in_gamut && self.contains(&current) {
+                min = chroma;
+                continue;
+            }
+            clipped = clip(self.from_oklch(&current));
+            let error = delta_e_ok(&self.to_oklch(clipped), &current);
+            if error < JND {
+                if JND - error < CHROMA_EPSILON {
+                    return clipped;
+                }
+                min_in_gamut = false;
+                min = chroma;
+            } else {
+                max = chroma;
+            }
+        }
+        clipped
+    }
+
+    /// Bring a color into this gamut by reducing its chroma alone
+    ///
+    /// Unlike `map`, the result is never clipped, so lightness and hue are
+    /// kept exactly. Used for 8-bit sRGB output, where rounding already adds
+    /// an error of its own. Returns gamma-encoded components from 0.0 to 1.0.
+    pub fn reduce_chroma(&self, color: &Oklch) -> [f64; 3] {
+        let clip = |components: [f64; 3]| components.map(|c| c.clamp(0.0, 1.0));
+
+        if color.l >= 1.0 {
+            return [1.0; 3];
+        }
+        if color.l <= 0.0 {
+            return [0.0; 3];
+        }
+
+        let mut current = *color;
+        if !self.contains(&current) {
+            let (mut min, mut max) = (0.0, color.c);
+            while max - min > CHROMA_EPSILON {
+                current.c = (min + max) / 2.0;
+                if self.contains(&current) {
+                    min = current.c;
+                } else {
+                    max = current.c;
+                }
+            }
+            current.c = min;
+        }
+        clip(self.from_oklch(&current))
+    }
+}
+
+/// Euclidean distance in OKLab
+fn delta_e_ok(a: &Oklch, b: &Oklch) -> f64 {
+    let (a, b) = (a.to_oklab(), b.to_oklab());
+    ((a.l - b.l).powi(2) + (a.a - b.a).powi(2) + (a.b - b.b).powi(2)).sqrt()
+}
+
+/// sRGB transfer function, extended to negative values
+fn encode(linear: f64) -> f64 {
+    let abs = linear.abs();
+    let encoded = if abs <= 0.0031308 {
+        abs * 12.92
+    } else {
+        1.055 * abs.powf(1.0 / 2.4) - 0.055
+    };
+    encoded.copysign(linear)
+}
+
+/// Inverse of the sRGB transfer function, extended to negative values
+fn decode(encoded: f64) -> f64 {
+    let abs = encoded.abs();
+    let linear = if abs <= 0.04045 {
+        abs / 12.92
+    } else {
+        ((abs + 0.055) / 1.055).powf(2.4)
+    };
+    linear.copysign(encoded)
+}
+
+/// 8-bit sRGB color of gamma-encoded sRGB components
+pub(crate) fn rgb_from_components(components: [f64; 3]) -> Rgb {
+    let [r, g, b] = components.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+    Rgb { r, g, b }
+}
+
+/// 8-bit sRGB color of an XYZ color (D65, Y in 0-100), gamut mapped to sRGB
+pub(crate) fn rgb_from_xyz(xyz: [f64; 3]) -> Rgb {
+    let oklch = Oklab::from_linear_srgb(Gamut::Srgb.linear_from_xyz(xyz)).to_oklch();
+    rgb_from_components(Gamut::Srgb.reduce_chroma(&oklch))
+}
+
+/// A color in Display P3, with gamma-encoded components from 0.0 to 1.0
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DisplayP3 {
+    pub r: f64,
+    pub g: f64,
+    pub b: f64,
+}
+
+impl DisplayP3 {
+    /// The same color as an sRGB color (every sRGB color is inside Display P3)
+    pub fn from_rgb(rgb: &Rgb) -> Self {
+        let srgb = [rgb.r, rgb.g, rgb.b].map(|c| c as f64 / 255.0);
+        let xyz = Gamut::Srgb.linear_to_xyz(srgb.map(decode));
+        Self::from_xyz(xyz)
+    }
+
+    /// Convert from XYZ (D65, Y in 0-100), without gamut mapping
+    pub fn from_xyz(xyz: [f64; 3]) -> Self {
+        let [r, g, b] = Gamut::DisplayP3.linear_from_xyz(xyz).map(encode);
+        Self { r, g, b }
+    }
+
+    /// Convert from OKLCH, gamut mapping to Display P3
+    pub fn from_oklch(color: &Oklch) -> Self {
+        let [r, g, b] = Gamut::DisplayP3.map(color);
+        Self { r, g, b }
+    }
+
+    pub fn to_xyz(&self) -> [f64; 3] {
+        Gamut::DisplayP3.linear_to_xyz([self.r, self.g, self.b].map(decode))
+    }
+
+    pub fn in_gamut(&self) -> bool {
+        const EPSILON: f64 = 1e-6;
+        [self.r, self.g, self.b]
+            .iter()
+            .all(|c| (-EPSILON..=1.0 + EPSILON).contains(c))
+    }
+
+    /// CSS Color 4 syntax, e.g. `color(display-p3 0.9176 0.2000 0.1386)`
+    pub fn to_css(&self) -> String {
+        let [r, g, b] = [self.r, self.g, self.b].map(|c| c.clamp(0.0, 1.0));
+        format!("color(display-p3 {:.4} {:.4} {:.4})", r, g, b)
+    }
+}
@@ -0,0 +1,402 @@
+// HCT (Hue-Chroma-Tone) color space used by Material Design 3
+// Hue and chroma come from CAM16, tone is L* from CIELAB
+
+use super::cam16::{matrix_multiply, Cam16, ViewingConditions, SRGB_TO_XYZ, XYZ_TO_CAM16RGB};
+use super::gamut::DisplayP3;
+use super::{clamp, delinearized, lstar_from_y, sanitize_degrees, y_from_lstar, Rgb};
+use std::f64::consts::PI;
+use std::sync::OnceLock;
+
+/// Represents a color in the HCT (Hue-Chroma-Tone) color space
+#[derive(Debug, Clone)]
+pub struct Hct {
+    pub h: f64, // Hue (0-360)
+    pub c: f64, // Chroma (0-100+)
+    pub t: f64, // Tone (0-100, equivalent to L* in L*a*b*)
+}
+
+impl Hct {
+    /// Create an HCT color from hue, chroma, and tone values
+    pub fn from_hct(h: f64, c: f64, t: f64) -> Self {
+        Self {
+            h: sanitize_degrees(h),
+            c: clamp(c, 0.0, 200.0), // Chroma can go higher than 100
+            t: clamp(t, 0.0, 100.0),
+        }
+    }
+
+    /// Convert HCT to RGB
+    ///
+    /// The tone is always preserved exactly. If the requested chroma is not
+    /// reachable at this hue and tone, the closest in-gamut chroma is used.
+    pub fn to_rgb(&self) -> Rgb {
+        solve_to_rgb(self.h, self.c, self.t)
+    }
+
+    /// Convert HCT to HEX
+    pub fn to_hex(&self) -> String {
+        let rgb = self.to_rgb();
+        super::rgb_to_hex(rgb.r as f64, rgb.g as f64, rgb.b as f64)
+    }
+
+    /// Convert HCT to Display P3
+    ///
+    /// Chroma beyond sRGB is kept as far as Display P3 can show it; past that,
+    /// chroma is reduced at constant hue and tone.
+    pub fn to_display_p3(&self) -> DisplayP3 {
+        if !(0.0001..=99.9999).contains(&self.t) {
+            return DisplayP3::from_rgb(&self.to_rgb());
+        }
+        let target_y = y_from_lstar(self.t);
+        let xyz_at_chroma = |chroma: f64| {
+            // Newton's method on J, as in `find_result_by_j`; P3 components
+            // are not bounded here, the caller checks the gamut
+            let mut j = target_y.sqrt() * 11.0;
+            let mut xyz = [0.0; 3];
+            for _ in 0..8 {
+                xyz = Cam16::from_jch(j, chroma, self.h)
+                    .xyz_in_viewing_conditions(ViewingConditions::standard());
+                if xyz[1] <= 0.0 || (xyz[1] - target_y).abs() < 1e-6 {
+                    break;
+                }
+                j -= (xyz[1] - target_y) * j / (2.0 * xyz[1]);
+            }
+            xyz
+        };
+
+        let p3 = DisplayP3::from_xyz(xyz_at_chroma(self.c));
+        if p3.in_gamut() {
+            return p3;
+        }
+        let (mut low, mut high) = (0.0, self.c);
+        let mut best = DisplayP3::from_xyz(xyz_at_chroma(0.0));
+        for _ in 0..20 {
+            let chroma = (low + high) / 2.0;
+            let p3 = DisplayP3::from_xyz(xyz_at_chroma(chroma));
+            if p3.in_gamut() {
+                best = p3;
+                low = chroma;
+            } else {
+                high = chroma;
+            }
+        }
+        best
+    }
+
+    /// The HCT values of the color `to_rgb` actually produces
+    pub fn in_gamut(&self) -> Hct {
+        let rgb = self.to_rgb();
+        rgb_to_hct(rgb.r, rgb.g, rgb.b)
+    }
+}
+
+/// Generate HCT color from RGB
+pub fn rgb_to_hct(r: u8, g: u8, b: u8) -> Hct {
+    let rgb = Rgb { r, g, b };
+    let cam = Cam16::from_rgb(&rgb);
+    let xyz = super::cam16::rgb_to_xyz(&rgb);
+    Hct {
+        h: cam.hue,
+        c: cam.chroma,
+        t: lstar_from_y(xyz[1]),
+    }
+}
+
+// HCT solver
+// Finds the sRGB color for a given hue, chroma and tone by first trying an
+// exact solution via Newton's method on J, and otherwise bisecting along the
+// edges of the sRGB cube at the requested tone to find the most chromatic
+// in-gamut color with the requested hue.
+
+struct SolverConstants {
+    scaled_discount_from_linrgb: [[f64; 3]; 3],
+    linrgb_from_scaled_discount: [[f64; 3]; 3],
+}
+
+const Y_FROM_LINRGB: [f64; 3] = SRGB_TO_XYZ[1];
+
+fn solver_constants() -> &'static SolverConstants {
+    static CONSTANTS: OnceLock<SolverConstants> = OnceLock::new();
+    CONSTANTS.get_or_init(|| {
+        let vc = ViewingConditions::standard();
+        let mut m = [[0.0; 3]; 3];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                let mut sum = 0.0;
+                for (k, xyz_row) in SRGB_TO_XYZ.iter().enumerate() {
+                    sum += XYZ_TO_CAM16RGB[i][k] * xyz_row[j];
+                }
+                *value = sum * vc.rgb_d[i] * vc.fl / 100.0;
+            }
+        }
+        SolverConstants {
+            scaled_discount_from_linrgb: m,
+            linrgb_from_scaled_discount: invert_3x3(&m),
+        }
+    })
+}
+
+fn invert_3x3(m: &[[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let det = m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+        - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+        + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0]);
+    let inv = 1.0 / det;
+    [
+        [
+            (m[1][1] * m[2][2] - m[1][2] * m[2][1]) * inv,
+            (m[0][2] * m[2][1] - m[0][1] * m[2][2]) * inv,
+            (m[0][1] * m[1][2] - m[0][2] * m[1][1]) * inv,
+        ],
+        [
+            (m[1][2] * m[2][0] - m[1][0] * m[2][2]) * inv,
+            (m[0][0] * m[2][2] - m[0][2] * m[2][0]) * inv,
+            (m[0][2] * m[1][0] - m[0][0] * m[1][2]) * inv,
+        ],
+        [
+            (m[1][0] * m[2][1] - m[1][1] * m[2][0]) * inv,
+            (m[0][1] * m[2][0] - m[0][0] * m[2][1]) * inv,
+            (m[0][0] * m[1][1] - m[0][1] * m[1][0]) * inv,
+        ],
+    ]
+}
+
+fn sanitize_radians(angle: f64) -> f64 {
+    (angle + PI * 8.0) % (PI * 2.0)
+}
+
+fn true_delinearized(component: f64) -> f64 {
+    let normalized = component / 100.0;
+    let delinearized = if normalized <= 0.0031308 {
+        normalized * 12.92
+    } else {
+        1.055 * normalized.powf(1.0 / 2.4) - 0.055
+    };
+    delinearized * 255.0
+}
+
+fn chromatic_adaptation(component: f64) -> f64 {
+    let af = component.abs().powf(0.42);
+    component.signum() * 400.0 * af / (af + 27.13)
+}
+
+fn inverse_chromatic_adaptation(adapted: f64) -> f64 {
+    let adapted_abs = adapted.abs();
+    let base = (27.13 * adapted_abs / (400.0 - adapted_abs)).max(0.0);
+    adapted.signum() * base.powf(1.0 / 0.42)
+}
+
+/// The hue of a linear RGB color, in radians
+fn hue_of(linrgb: [f64; 3]) -> f64 {
+    let scaled = matrix_multiply(linrgb, &solver_constants().scaled_discount_from_linrgb);
+    let r_a = chromatic_adaptation(scaled[0]);
+    let g_a = chromatic_adaptation(scaled[1]);
+    let b_a = chromatic_adaptation(scaled[2]);
+    let a = (11.0 * r_a + -12.0 * g_a + b_a) / 11.0;
+    let b = (r_a + g_a - 2.0 * b_a) / 9.0;
+    b.atan2(a)
+}
+
+fn are_in_cyclic_order(a: f64, b: f64, c: f64) -> bool {
+    let delta_a_b = sanitize_radians(b - a);
+    let delta_a_c = sanitize_radians(c - a);
+    delta_a_b < delta_a_c
+}
+
+fn set_coordinate(source: [f64; 3], coordinate: f64, target: [f64; 3], axis: usize) -> [f64; 3] {
+    let t = (coordinate - source[axis]) / (target[axis] - source[axis]);
+    [
+        source[0] + (target[0] - source[0]) * t,
+        source[1] + (target[1] - source[1]) * t,
+        source[2] + (target[2] - source[2]) * t,
+    ]
+}
+
+fn is_bounded(x: f64) -> bool {
+    (0.0..=100.0).contains(&x)
+}
+
+/// The nth possible vertex of the polygon formed by intersecting the plane of
+/// constant Y with the sRGB cube, or `None` if it lies outside the cube
+fn nth_vertex(y: f64, n: usize) -> Option<[f64; 3]> {
+    let [k_r, k_g, k_b] = Y_FROM_LINRGB;
+    let coord_a = if n % 4 <= 1 { 0.0 } else { 100.0 };
+    let coord_b = if n.is_multiple_of(2) { 0.0 } else { 100.0 };
+    let vertex = if n < 4 {
+        let (g, b) = (coord_a, coord_b);
+        [(y - g * k_g - b * k_b) / k_r, g, b]
+    } else if n < 8 {
+        let (b, r) = (coord_a, coord_b);
+        [r, (y - r * k_r - b * k_b) / k_g, b]
+    } else {
+        let (r, g) = (coord_a, coord_b);
+        [r, g, (y - r * k_r - g * k_g) / k_b]
+    };
+    if vertex.iter().all(|&v| is_bounded(v)) {
+        Some(vertex)
+    } else {
+        None
+    }
+}
+
+/// Find the segment of the constant-Y polygon containing the target hue
+fn bisect_to_segment(y: f64, target_hue: f64) -> ([f64; 3], [f64; 3]) {
+    let mut segment: Option<([f64; 3], f64, [f64; 3], f64)> = None;
+    let mut uncut = true;
+    for n in 0..12 {
+        let Some(mid) = nth_vertex(y, n) else {
+            continue;
+        };
+        let mid_hue = hue_of(mid);
+        let Some((left, left_hue, right, right_hue)) = segment.as_mut() else {
+            segment = Some((mid, mid_hue, mid, mid_hue));
+            continue;
+        };
+        if uncut || are_in_cyclic_order(*left_hue, mid_hue, *right_hue) {
+            uncut = false;
+            if are_in_cyclic_order(*left_hue, target_hue, mid_hue) {
+                *right = mid;
+                *right_hue = mid_hue;
+            } else {
+                *left = mid;
+                *left_hue = mid_hue;
+            }
+        }
+    }
+    let (left, _, right, _) = segment.unwrap_or(([0.0; 3], 0.0, [0.0; 3], 0.0));
+    (left, right)
+}
+
+fn critical_plane(index: i64) -> f64 {
+    // Linear value of the midpoint between two adjacent 8-bit sRGB values
+    let normalized = (index as f64 + 0.5) / 255.0;
+    let linear = if normalized <= 0.040449936 {
+        normalized / 12.92
+    } else {
+        ((normalized + 0.055) / 1.055).powf(2.4)
+    };
+    linear * 100.0
+}
+
+fn bisect_to_limit(y: f64, target_hue: f64) -> [f64; 3] {
+    let (mut left, mut right) = bisect_to_segment(y, target_hue);
+    let mut left_hue = hue_of(left);
+    for axis in 0..3 {
+        if left[axis] == right[axis] {
+            continue;
+        }
+        let (mut l_plane, mut r_plane) = if left[axis] < right[axis] {
+            (
+                (true_delinearized(left[axis]) - 0.5).floor() as i64,
+                (true_delinearized(right[axis]) - 0.5).ceil() as i64,
+            )
+        } else {
+            (
+                (true_delinearized(left[axis]) - 0.5).ceil() as i64,
+                (true_delinearized(right[axis]) - 0.5).floor() as i64,
+            )
+        };
+        for _ in 0..8 {
+            if (r_plane - l_plane).abs() <= 1 {
+                break;
+            }
+            let m_plane = (l_plane + r_plane).div_euclid(2);
+            let mid = set_coordinate(left, critical_plane(m_plane), right, axis);
+            let mid_hue = hue_of(mid);
+            if are_in_cyclic_order(left_hue, target_hue, mid_hue) {
+                right = mid;
+                r_plane = m_plane;
+            } else {
+                left = mid;
+                left_hue = mid_hue;
+                l_plane = m_plane;
+            }
+        }
+    }
+    [
+        (left[0] + right[0]) / 2.0,
+        (left[1] + right[1]) / 2.0,
+        (left[2] + right[2]) / 2.0,
+    ]
+}
+
+fn linrgb_to_rgb(linrgb: [f64; 3]) -> Rgb {
+    Rgb {
+        r: delinearized(linrgb[0]),
+        g: delinearized(linrgb[1]),
+        b: delinearized(linrgb[2]),
+    }
+}
+
+/// Solve for the exact color with the given hue, chroma and Y using Newton's method on J
+fn find_result_by_j(hue_radians: f64, chroma: f64, y: f64) -> Option<Rgb> {
+    let vc = ViewingConditions::standard();
+    let constants = solver_constants();
+
+    // Initial estimate of J
+    let mut j = y.sqrt() * 11.0;
+    let t_inner_coeff = 1.0 / (1.64 - 0.29f64.powf(vc.n)).powf(0.73);
+    let e_hue = 0.25 * ((hue_radians + 2.0).cos() + 3.8);
+    let p1 = e_hue * (50000.0 / 13.0) * vc.nc * vc.ncb;
+    let h_sin = hue_radians.sin();
+    let h_cos = hue_radians.cos();
+
+    for iteration in 0..5 {
+        let j_normalized = j / 100.0;
+        let alpha = if chroma == 0.0 || j == 0.0 {
+            0.0
+        } else {
+            chroma / j_normalized.sqrt()
+        };
+        let t = (alpha * t_inner_coeff).powf(1.0 / 0.9);
+        let ac = vc.aw * j_normalized.powf(1.0 / vc.c / vc.z);
+        let p2 = ac / vc.nbb;
+        let gamma = 23.0 * (p2 + 0.305) * t / (23.0 * p1 + 11.0 * t * h_cos + 108.0 * t * h_sin);
+        let a = gamma * h_cos;
+        let b = gamma * h_sin;
+        let r_a = (460.0 * p2 + 451.0 * a + 288.0 * b) / 1403.0;
+        let g_a = (460.0 * p2 - 891.0 * a - 261.0 * b) / 1403.0;
+        let b_a = (460.0 * p2 - 220.0 * a - 6300.0 * b) / 1403.0;
+        let scaled = [
+            inverse_chromatic_adaptation(r_a),
+            inverse_chromatic_adaptation(g_a),
+            inverse_chromatic_adaptation(b_a),
+        ];
+        let linrgb = matrix_multiply(scaled, &constants.linrgb_from_scaled_discount);
+        if linrgb.iter().any(|&v| v < 0.0) {
+            return None;
+        }
+        let fnj = Y_FROM_LINRGB[0] * linrgb[0]
+            + Y_FROM_LINRGB[1] * linrgb[1]
+            + Y_FROM_LINRGB[2] * linrgb[2];
+        if fnj <= 0.0 {
+            return None;
+        }
+        if iteration == 4 || (fnj - y).abs() < 0.002 {
+            if linrgb.iter().any(|&v| v > 100.01) {
+                return None;
+            }
+            return Some(linrgb_to_rgb(linrgb));
+        }
+        // Newton step on J, using 2 * fn(j) / j as the approximate derivative
+        j -= (fnj - y) * j / (2.0 * fnj);
+    }
+    None
+}
+
+/// Find the sRGB color with the given hue, chroma and tone, reducing chroma if needed
+fn solve_to_rgb(hue_degrees: f64, chroma: f64, lstar: f64) -> Rgb {
+    if chroma < 0.0001 || !(0.0001..=99.9999).contains(&lstar) {
+        let gray = delinearized(y_from_lstar(lstar));
+        return Rgb {
+            r: gray,
+            g: gray,
+            b: gray,
+        };
+    }
+    let hue_radians = sanitize_degrees(hue_degrees).to_radians();
+    let y = y_from_lstar(lstar);
+    if let Some(rgb) = find_result_by_j(hue_radians, chroma, y) {
+        return rgb;
+    }
+    linrgb_to_rgb(bisect_to_limit(y, hue_radians))
+}
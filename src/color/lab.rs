@@ -0,0 +1,180 @@
+// CIELAB and CIE LCh(ab) color spaces relative to the D65 white point
+// Includes the CIEDE2000 color difference formula
+
+use super::cam16::{rgb_to_xyz, WHITE_POINT_D65};
+use super::gamut::rgb_from_xyz;
+use super::{sanitize_degrees, Rgb};
+
+/// A color in CIELAB, with L* from 0 to 100
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lab {
+    pub l: f64,
+    pub a: f64,
+    pub b: f64,
+}
+
+/// A color in CIE LCh(ab), the polar form of CIELAB, with hue in degrees
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lch {
+    pub l: f64,
+    pub c: f64,
+    pub h: f64,
+}
+
+const EPSILON: f64 = 216.0 / 24389.0;
+const KAPPA: f64 = 24389.0 / 27.0;
+
+fn lab_f(t: f64) -> f64 {
+    if t > EPSILON {
+        t.cbrt()
+    } else {
+        (KAPPA * t + 16.0) / 116.0
+    }
+}
+
+fn lab_f_inv(ft: f64) -> f64 {
+    let ft3 = ft * ft * ft;
+    if ft3 > EPSILON {
+        ft3
+    } else {
+        (116.0 * ft - 16.0) / KAPPA
+    }
+}
+
+impl Lab {
+    pub fn from_rgb(rgb: &Rgb) -> Self {
+        let [x, y, z] = rgb_to_xyz(rgb);
+        Self::from_xyz(x, y, z)
+    }
+
+    /// Convert from CIE XYZ, with Y from 0 to 100
+    pub fn from_xyz(x: f64, y: f64, z: f64) -> Self {
+        let fx = lab_f(x / WHITE_POINT_D65[0]);
+        let fy = lab_f(y / WHITE_POINT_D65[1]);
+        let fz = lab_f(z / WHITE_POINT_D65[2]);
+        Self {
+            l: 116.0 * fy - 16.0,
+            a: 500.0 * (fx - fy),
+            b: 200.0 * (fy - fz),
+        }
+    }
+
+    /// Convert to CIE XYZ, with Y from 0 to 100
+    pub fn to_xyz(&self) -> [f64; 3] {
+        let fy = (self.l + 16.0) / 116.0;
+        let fx = self.a / 500.0 + fy;
+        let fz = fy - self.b / 200.0;
+        [
+            lab_f_inv(fx) * WHITE_POINT_D65[0],
+            lab_f_inv(fy) * WHITE_POINT_D65[1],
+            lab_f_inv(fz) * WHITE_POINT_D65[2],
+        ]
+    }
+
+    /// Convert to sRGB, reducing chroma at constant lightness and hue if out of gamut
+    pub fn to_rgb(&self) -> Rgb {
+        rgb_from_xyz(self.to_xyz())
+    }
+
+    pub fn to_lch(&self) -> Lch {
+        let c = (self.a * self.a + self.b * self.b).sqrt();
+        let h = if c < 1e-8 {
+            0.0
+        } else {
+            sanitize_degrees(self.b.atan2(self.a).to_degrees())
+        };
+        Lch { l: self.l, c, h }
+    }
+}
+
+impl Lch {
+    pub fn from_rgb(rgb: &Rgb) -> Self {
+        Lab::from_rgb(rgb).to_lch()
+    }
+
+    pub fn to_lab(&self) -> Lab {
+        let h = self.h.to_radians();
+        Lab {
+            l: self.l,
+            a: self.c * h.cos(),
+            b: self.c * h.sin(),
+        }
+    }
+
+    /// Convert to sRGB, reducing chroma at constant lightness and hue if out of gamut
+    pub fn to_rgb(&self) -> Rgb {
+        self.to_lab().to_rgb()
+    }
+}
+
+/// CIEDE2000 color difference between two colors
+///
+/// A difference below about 1.0 is not noticeable; around 2.3 is a just
+/// noticeable difference.
+pub fn delta_e_2000(lab1: &Lab, lab2: &Lab) -> f64 {
+    let pow7 = |x: f64| x.powi(7);
+    const POW7_25: f64 = 6_103_515_625.0; // 25^7
+
+    let c1 = (lab1.a * lab1.a + lab1.b * lab1.b).sqrt();
+    let c2 = (lab2.a * lab2.a + lab2.b * lab2.b).sqrt();
+    let c_mean = (c1 + c2) / 2.0;
+    let g = 0.5 * (1.0 - (pow7(c_mean) / (pow7(c_mean) + POW7_25)).sqrt());
+
+    let a1 = (1.0 + g) * lab1.a;
+    let a2 = (1.0 + g) * lab2.a;
+    let c1 = (a1 * a1 + lab1.b * lab1.b).sqrt();
+    let c2 = (a2 * a2 + lab2.b * lab2.b).sqrt();
+    let hue = |b: f64, a: f64| {
+        if b == 0.0 && a == 0.0 {
+            0.0
+        } else {
+            sanitize_degrees(b.atan2(a).to_degrees())
+        }
+    };
+    let h1 = hue(lab1.b, a1);
+    let h2 = hue(lab2.b, a2);
+
+    let delta_l = lab2.l - lab1.l;
+    let delta_c = c2 - c1;
+    let chroma_product = c1 * c2;
+    let delta_h_angle = if chroma_product == 0.0 {
+        0.0
+    } else if (h2 - h1).abs() <= 180.0 {
+        h2 - h1
+    } else if h2 - h1 > 180.0 {
+        h2 - h1 - 360.0
+    } else {
+        h2 - h1 + 360.0
+    };
+    let delta_h = 2.0 * chroma_product.sqrt() * (delta_h_angle.to_radians() / 2.0).sin();
+
+    let l_mean = (lab1.l + lab2.l) / 2.0;
+    let c_mean = (c1 + c2) / 2.0;
+    let h_mean = if chroma_product == 0.0 {
+        h1 + h2
+    } else if (h1 - h2).abs() <= 180.0 {
+        (h1 + h2) / 2.0
+    } else if h1 + h2 < 360.0 {
+        (h1 + h2 + 360.0) / 2.0
+    } else {
+        (h1 + h2 - 360.0) / 2.0
+    };
+
+    let cos_deg = |degrees: f64| degrees.to_radians().cos();
+    let t = 1.0 - 0.17 * cos_deg(h_mean - 30.0)
+        + 0.24 * cos_deg(2.0 * h_mean)
+        + 0.32 * cos_deg(3.0 * h_mean + 6.0)
+        - 0.20 * cos_deg(4.0 * h_mean - 63.0);
+    let delta_theta = 30.0 * (-((h_mean - 275.0) / 25.0).powi(2)).exp();
+    let r_c = 2.0 * (pow7(c_mean) / (pow7(c_mean) + POW7_25)).sqrt();
+    let l_offset = (l_mean - 50.0).powi(2);
+    let s_l = 1.0 + 0.015 * l_offset / (20.0 + l_offset).sqrt();
+    let s_c = 1.0 + 0.045 * c_mean;
+    let s_h = 1.0 + 0.015 * c_mean * t;
+    let r_t = -(2.0 * delta_theta).to_radians().sin() * r_c;
+
+    let l_term = delta_l / s_l;
+    let c_term = delta_c / s_c;
+    let h_term = delta_h / s_h;
+    (l_term * l_term + c_term * c_term + h_term * h_term + r_t * c_term * h_term).sqrt()
+}
@@ -0,0 +1,116 @@
+// OKLab and OKLCH color spaces (Björn Ottosson, 2020)
+// Perceptually uniform spaces used by CSS Color 4's oklab() and oklch() functions
+
+use super::gamut::{rgb_from_components, DisplayP3, Gamut};
+use super::{linearized, sanitize_degrees, Rgb};
+
+/// A color in OKLab, with lightness from 0.0 to 1.0
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Oklab {
+    pub l: f64,
+    pub a: f64,
+    pub b: f64,
+}
+
+/// A color in OKLCH, the polar form of OKLab, with hue in degrees
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Oklch {
+    pub l: f64,
+    pub c: f64,
+    pub h: f64,
+}
+
+impl Oklab {
+    pub fn from_rgb(rgb: &Rgb) -> Self {
+        let [r, g, b] = [rgb.r, rgb.g, rgb.b].map(|c| linearized(c) / 100.0);
+        Self::from_linear_srgb([r, g, b])
+    }
+
+    /// Convert from linear sRGB, with components from 0.0 to 1.0
+    pub fn from_linear_srgb([r, g, b]: [f64; 3]) -> Self {
+        let l = 0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b;
+        let m = 0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b;
+        let s = 0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b;
+
+        let (l, m, s) = (l.cbrt(), m.cbrt(), s.cbrt());
+        Self {
+            l: 0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+            a: 1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+            b: 0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+        }
+    }
+
+    /// Convert to linear sRGB; components outside 0.0-1.0 are out of gamut
+    pub fn to_linear_srgb(&self) -> [f64; 3] {
+        let l = self.l + 0.3963377774 * self.a + 0.2158037573 * self.b;
+        let m = self.l - 0.1055613458 * self.a - 0.0638541728 * self.b;
+        let s = self.l - 0.0894841775 * self.a - 1.2914855480 * self.b;
+
+        let (l, m, s) = (l * l * l, m * m * m, s * s * s);
+        [
+            4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+            -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+            -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+        ]
+    }
+
+    /// Convert to sRGB
+    ///
+    /// Out of gamut colors are brought in the same way as `Oklch::to_rgb`.
+    pub fn to_rgb(&self) -> Rgb {
+        self.to_oklch().to_rgb()
+    }
+
+    pub fn to_oklch(&self) -> Oklch {
+        let c = (self.a * self.a + self.b * self.b).sqrt();
+        let h = if c < 1e-8 {
+            0.0
+        } else {
+            sanitize_degrees(self.b.atan2(self.a).to_degrees())
+        };
+        Oklch { l: self.l, c, h }
+    }
+
+    /// CSS Color 4 syntax, e.g. `oklab(62.80% 0.2249 0.1258)`
+    pub fn to_css(&self) -> String {
+        format!("oklab({:.2}% {:.4} {:.4})", self.l * 100.0, self.a, self.b)
+    }
+}
+
+impl Oklch {
+    pub fn from_rgb(rgb: &Rgb) -> Self {
+        Oklab::from_rgb(rgb).to_oklch()
+    }
+
+    pub fn to_oklab(&self) -> Oklab {
+        let h = self.h.to_radians();
+        Oklab {
+            l: self.l,
+            a: self.c * h.cos(),
+            b: self.c * h.sin(),
+        }
+    }
+
+    /// Whether this color can be shown in sRGB without clipping
+    pub fn in_srgb_gamut(&self) -> bool {
+        Gamut::Srgb.contains(self)
+    }
+
+    /// Convert to sRGB
+    ///
+    /// Out of gamut colors keep their lightness and hue; chroma is reduced
+    /// until the color fits in sRGB.
+    pub fn to_rgb(&self) -> Rgb {
+        rgb_from_components(Gamut::Srgb.reduce_chroma(self))
+    }
+
+    /// Convert to Display P3, reducing chroma if the color is outside it
+    pub fn to_display_p3(&self) -> DisplayP3 {
+        DisplayP3::from_oklch(self)
+    }
+
+    /// CSS Color 4 syntax, e.g. `oklch(62.80% 0.2577 29.23)`
+    pub fn to_css(&self) -> String {
+        format!("oklch({:.2}% {:.4} {:.2})", self.l * 100.0, self.c, self.h)
+    }
+}
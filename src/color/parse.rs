@@ -0,0 +1,368 @@
+// Parsing of colors written in CSS syntax, with alpha
+
+use super::{clamp, hsl_to_rgb, sanitize_degrees, Rgb};
+
+/// An RGB color with an alpha channel (0 = transparent, 255 = opaque)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Rgba {
+    /// The color without its alpha channel
+    pub fn rgb(&self) -> Rgb {
+        Rgb {
+            r: self.r,
+            g: self.g,
+            b: self.b,
+        }
+    }
+
+    /// Alpha as a fraction from 0.0 to 1.0
+    pub fn alpha(&self) -> f64 {
+        self.a as f64 / 255.0
+    }
+
+    /// The opaque color seen when this one is drawn over a backdrop
+    pub fn over(&self, backdrop: &Rgb) -> Rgb {
+        let alpha = self.alpha();
+        let blend = |channel: u8, backdrop: u8| {
+            (channel as f64 * alpha + backdrop as f64 * (1.0 - alpha)).round() as u8
+        };
+        Rgb {
+            r: blend(self.r, backdrop.r),
+            g: blend(self.g, backdrop.g),
+            b: blend(self.b, backdrop.b),
+        }
+    }
+}
+
+impl Rgb {
+    pub fn with_alpha(self, a: u8) -> Rgba {
+        Rgba {
+            r: self.r,
+            g: self.g,
+            b: self.b,
+            a,
+        }
+    }
+}
+
+impl From<Rgb> for Rgba {
+    fn from(rgb: Rgb) -> Self {
+        rgb.with_alpha(255)
+    }
+}
+
+impl std::str::FromStr for Rgba {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_color(s)
+    }
+}
+
+/// Parse a color in any of the supported CSS syntaxes
+///
+/// Accepts `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa` (the `#` is optional),
+/// `rgb()`/`rgba()` and `hsl()`/`hsla()` in both the comma and the space
+/// separated syntax, and CSS named colors.
+pub fn parse_color(input: &str) -> Result<Rgba, String> {
+    let trimmed = input.trim();
+    let lower = trimmed.to_ascii_lowercase();
+
+    let parsed = if let Some(args) = function_arguments(&lower, &["rgb", "rgba"]) {
+        parse_rgb_function(args)
+    } else if let Some(args) = function_arguments(&lower, &["hsl", "hsla"]) {
+        parse_hsl_function(args)
+    } else if let Some(rgba) = named_color(&lower) {
+        Some(rgba)
+    } else {
+        parse_hex(&lower)
+    };
+
+    parsed.ok_or_else(|| {
+        format!(
+            "Invalid color: {}. Expected a hex color, rgb(), rgba(), hsl(), hsla() or a CSS color name.",
+            trimmed
+        )
+    })
+}
+
+fn parse_hex(input: &str) -> Option<Rgba> {
+    let hex = input.strip_prefix('#').unwrap_or(input);
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|d| d * 17);
+    let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+
+    match hex.len() {
+        3 => Some(Rgba {
+            r: digit(0)?,
+            g: digit(1)?,
+            b: digit(2)?,
+            a: 255,
+        }),
+        4 => Some(Rgba {
+            r: digit(0)?,
+            g: digit(1)?,
+            b: digit(2)?,
+            a: digit(3)?,
+        }),
+        6 => Some(Rgba {
+            r: byte(0)?,
+            g: byte(2)?,
+            b: byte(4)?,
+            a: 255,
+        }),
+        8 => Some(Rgba {
+            r: byte(0)?,
+            g: byte(2)?,
+            b: byte(4)?,
+            a: byte(6)?,
+        }),
+        _ => None,
+    }
+}
+
+/// The arguments of a CSS function call like `rgb(...)`, if `input` calls one of `names`
+fn function_arguments<'a>(input: &'a str, names: &[&str]) -> Option<Vec<&'a str>> {
+    let open = input.find('(')?;
+    if !names.contains(&input[..open].trim()) || !input.ends_with(')') {
+        return None;
+    }
+    let inner = &input[open + 1..input.len() - 1];
+
+    // Either `a, b, c, alpha` or `a b c / alpha`
+    let args: Vec<&str> = if inner.contains(',') {
+        inner.split(',').map(str::trim).collect()
+    } else {
+        let (color, alpha) = match inner.split_once('/') {
+            Some((color, alpha)) => (color, Some(alpha.trim())),
+            None => (inner, None),
+        };
+        color.split_whitespace().chain(alpha).collect()
+    };
+    Some(args)
+}
+
+/// A number, or a percentage scaled so that 100% is `percent_scale`
+fn parse_number(arg: &str, percent_scale: f64) -> Option<f64> {
+    let value = match arg.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>().ok()? / 100.0 * percent_scale,
+        None => arg.parse::<f64>().ok()?,
+    };
+    value.is_finite().then_some(value)
+}
+
+/// An alpha value (0-1 or a percentage) as an 8-bit channel, opaque when missing
+fn parse_alpha(arg: Option<&&str>) -> Option<u8> {
+    match arg {
+        Some(arg) => {
+            let alpha = clamp(parse_number(arg, 1.0)?, 0.0, 1.0);
+            Some((alpha * 255.0).round() as u8)
+        }
+        None => Some(255),
+    }
+}
+
+fn parse_rgb_function(args: Vec<&str>) -> Option<Rgba> {
+    if !(3..=4).contains(&args.len()) {
+        return None;
+    }
+    let channel = |arg: &str| parse_number(arg, 255.0).map(|v| clamp(v.round(), 0.0, 255.0) as u8);
+    Some(Rgba {
+        r: channel(args[0])?,
+        g: channel(args[1])?,
+        b: channel(args[2])?,
+        a: parse_alpha(args.get(3))?,
+    })
+}
+
+fn parse_hsl_function(args: Vec<&str>) -> Option<Rgba> {
+    if !(3..=4).contains(&args.len()) {
+        return None;
+    }
+    let hue = args[0].strip_suffix("deg").unwrap_or(args[0]);
+    let h = sanitize_degrees(hue.trim().parse::<f64>().ok()?);
+    let s = clamp(parse_number(args[1], 100.0)?, 0.0, 100.0);
+    let l = clamp(parse_number(args[2], 100.0)?, 0.0, 100.0);
+    Some(hsl_to_rgb(h, s, l).with_alpha(parse_alpha(args.get(3))?))
+}
+
+fn named_color(name: &str) -> Option<Rgba> {
+    if name == "transparent" {
+        return Some(Rgba {
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 0,
+        });
+    }
+    let index = NAMED_COLORS
+        .binary_search_by_key(&name, |(named, _)| named)
+        .ok()?;
+    let value = NAMED_COLORS[index].1;
+    Some(Rgba {
+        r: (value >> 16) as u8,
+        g: (value >> 8) as u8,
+        b: value as u8,
+        a: 255,
+    })
+}
+
+/// CSS named colors, sorted by name
+const NAMED_COLORS: [(&str, u32); 148] = [
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
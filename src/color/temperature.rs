@@ -0,0 +1,186 @@
+// Color temperature model for analogous and complementary colors
+// Ported from material-color-utilities' TemperatureCache: colors are warm or
+// cool depending on their hue and chroma in CIELAB, and analogous colors are
+// found by walking the hue wheel in even steps of temperature
+
+use super::{rgb_to_hct, sanitize_degrees, Hct, Lab, Rgb};
+
+/// A color at a given hue, with its temperature
+#[derive(Debug, Clone)]
+struct Sample {
+    hct: Hct,
+    temperature: f64,
+}
+
+impl Sample {
+    fn new(hct: &Hct) -> Self {
+        let rgb = hct.to_rgb();
+        Self {
+            // The hue and chroma the color actually has after gamut mapping
+            hct: rgb_to_hct(rgb.r, rgb.g, rgb.b),
+            temperature: raw_temperature(&rgb),
+        }
+    }
+}
+
+/// Temperatures of every hue at the chroma and tone of an input color
+#[derive(Debug, Clone)]
+pub struct TemperatureCache {
+    input: Sample,
+    /// One sample per whole hue, from 0 to 360 inclusive
+    by_hue: Vec<Sample>,
+    coldest: Sample,
+    warmest: Sample,
+}
+
+/// Temperature of a color: negative is cool (blue), positive is warm (orange)
+///
+/// From Ou, Woodcock and Wright (2004), using CIELAB hue and chroma. Values
+/// range from about -0.5 for grays to about 2 for saturated warm colors.
+pub fn raw_temperature(rgb: &Rgb) -> f64 {
+    let lch = Lab::from_rgb(rgb).to_lch();
+    -0.5 + 0.02 * lch.c.powf(1.07) * sanitize_degrees(lch.h - 50.0).to_radians().cos()
+}
+
+/// Whether `angle` is on the arc going clockwise from `a` to `b`
+fn is_between(angle: f64, a: f64, b: f64) -> bool {
+    if a < b {
+        a <= angle && angle <= b
+    } else {
+        a <= angle || angle <= b
+    }
+}
+
+impl TemperatureCache {
+    pub fn new(input: &Hct) -> Self {
+        let input = Sample::new(input);
+        let by_hue: Vec<Sample> = (0..=360)
+            .map(|hue| Sample::new(&Hct::from_hct(hue as f64, input.hct.c, input.hct.t)))
+            .collect();
+
+        // Ties go to the first coldest and the last warmest, as with a stable sort
+        let all = || by_hue.iter().chain(std::iter::once(&input));
+        let coldest = all()
+            .min_by(|a, b| a.temperature.total_cmp(&b.temperature))
+            .unwrap_or(&input)
+            .clone();
+        let warmest = all()
+            .max_by(|a, b| a.temperature.total_cmp(&b.temperature))
+            .unwrap_or(&input)
+            .clone();
+
+        Self {
+            input,
+            by_hue,
+            coldest,
+            warmest,
+        }
+    }
+
+    fn at_hue(&self, hue: f64) -> &Sample {
+        &self.by_hue[(hue.round() as usize).min(360)]
+    }
+
+    fn relative(&self, temperature: f64) -> f64 {
+        let range = self.warmest.temperature - self.coldest.temperature;
+        // With no spread, e.g. at tone 100 where only white exists
+        if range == 0.0 {
+            return 0.5;
+        }
+        (temperature - self.coldest.temperature) / range
+    }
+
+    /// Temperature of a color relative to the input's hue wheel, from 0.0
+    /// (the coldest hue) to 1.0 (the warmest)
+    pub fn relative_temperature(&self, hct: &Hct) -> f64 {
+        self.relative(raw_temperature(&hct.to_rgb()))
+    }
+
+    /// The complementary color: on the other side of the wheel between the
+    /// coldest and warmest hues, at the opposite relative temperature
+    pub fn complement(&self) -> Hct {
+        let coldest_hue = self.coldest.hct.h;
+        let warmest_hue = self.warmest.hct.h;
+        let input_is_cold_to_warm = is_between(self.input.hct.h, coldest_hue, warmest_hue);
+        let (start_hue, end_hue) = if input_is_cold_to_warm {
+            (warmest_hue, coldest_hue)
+        } else {
+            (coldest_hue, warmest_hue)
+        };
+
+        let complement_temperature = 1.0 - self.relative(self.input.temperature);
+        let mut answer = self.at_hue(self.input.hct.h);
+        let mut smallest_error = f64::MAX;
+        for hue_addend in 0..=360 {
+            let hue = sanitize_degrees(start_hue + hue_addend as f64);
+            if !is_between(hue, start_hue, end_hue) {
+                continue;
+            }
+            let candidate = self.at_hue(hue);
+            let error = (complement_temperature - self.relative(candidate.temperature)).abs();
+            if error < smallest_error {
+                smallest_error = error;
+                answer = candidate;
+            }
+        }
+        answer.hct.clone()
+    }
+
+    /// `count` analogous colors centered on the input, which is included
+    ///
+    /// The hue wheel is split into `divisions` steps of equal temperature
+    /// change; neighbors are one step apart. MD3 uses 5 colors in 12 divisions
+    /// by default.
+    pub fn analogous_colors(&self, count: usize, divisions: usize) -> Vec<Hct> {
+        let start_hue = self.input.hct.h.round() as usize % 360;
+        let start = self.at_hue(start_hue as f64);
+        let hue_at = |addend: usize| self.at_hue(((start_hue + addend) % 360) as f64);
+
+        let mut total_change = 0.0;
+        let mut last = self.relative(start.temperature);
+        for addend in 0..360 {
+            let temperature = self.relative(hue_at(addend).temperature);
+            total_change += (temperature - last).abs();
+            last = temperature;
+        }
+
+        let step = total_change / divisions as f64;
+        let mut all_colors = vec![start];
+        let mut change = 0.0;
+        let mut last = self.relative(start.temperature);
+        let mut addend = 1;
+        while all_colors.len() < divisions {
+            let sample = hue_at(addend);
+            let temperature = self.relative(sample.temperature);
+            change += (temperature - last).abs();
+
+            // A hue may fill several steps, e.g. when there are fewer
+            // distinct hues than divisions
+            let mut index_addend = 0;
+            while all_colors.len() < divisions
+                && change >= (all_colors.len() + index_addend) as f64 * step
+            {
+                all_colors.push(sample);
+                index_addend += 1;
+            }
+            last = temperature;
+            addend += 1;
+
+            if addend > 360 {
+                all_colors.resize(divisions.max(1), sample);
+                break;
+            }
+        }
+
+        let counter_clockwise = (count.max(1) - 1) / 2;
+        let clockwise = count.max(1) - 1 - counter_clockwise;
+        let len = all_colors.len();
+        let mut answers: Vec<Hct> = (1..=counter_clockwise)
+            .rev()
+            .map(|i| all_colors[(len - i % len) % len].hct.clone())
+            .collect();
+        answers.push(self.input.hct.clone());
+        answers.extend((1..=clockwise).map(|i| all_colors[i % len].hct.clone()));
+        answers
+    }
+}
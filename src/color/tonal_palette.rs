@@ -0,0 +1,38 @@
+// Tonal palettes: every tone (0-100) of a single hue and chroma in HCT
+
+use super::{rgb_to_hct, Hct, Rgb};
+
+/// A key color's hue and chroma, from which any tone can be produced
+#[derive(Debug, Clone)]
+pub struct TonalPalette {
+    pub hue: f64,
+    pub chroma: f64,
+}
+
+impl TonalPalette {
+    /// Create a tonal palette from a hue and chroma
+    pub fn from_hue_and_chroma(hue: f64, chroma: f64) -> Self {
+        Self { hue, chroma }
+    }
+
+    /// Create a tonal palette from the hue and chroma of a color
+    pub fn from_rgb(rgb: &Rgb) -> Self {
+        let hct = rgb_to_hct(rgb.r, rgb.g, rgb.b);
+        Self::from_hue_and_chroma(hct.h, hct.c)
+    }
+
+    /// The HCT color of this palette at the given tone
+    pub fn hct(&self, tone: f64) -> Hct {
+        Hct::from_hct(self.hue, self.chroma, tone)
+    }
+
+    /// The color of this palette at the given tone
+    pub fn tone(&self, tone: f64) -> Rgb {
+        self.hct(tone).to_rgb()
+    }
+
+    /// The HEX color of this palette at the given tone
+    pub fn hex(&self, tone: f64) -> String {
+        self.hct(tone).to_hex()
+    }
+}
@@ -0,0 +1,84 @@
+// xterm 256-color and basic 16-color palettes
+// Colors are matched to the nearest index with CIEDE2000, so the match is the
+// one that looks closest rather than the one with the closest RGB values
+
+use super::{delta_e_2000, Lab, Rgb};
+use std::sync::OnceLock;
+
+/// xterm's default colors for the 16 basic indices
+const XTERM_16: [[u8; 3]; 16] = [
+    [0x00, 0x00, 0x00],
+    [0xcd, 0x00, 0x00],
+    [0x00, 0xcd, 0x00],
+    [0xcd, 0xcd, 0x00],
+    [0x00, 0x00, 0xee],
+    [0xcd, 0x00, 0xcd],
+    [0x00, 0xcd, 0xcd],
+    [0xe5, 0xe5, 0xe5],
+    [0x7f, 0x7f, 0x7f],
+    [0xff, 0x00, 0x00],
+    [0x00, 0xff, 0x00],
+    [0xff, 0xff, 0x00],
+    [0x5c, 0x5c, 0xff],
+    [0xff, 0x00, 0xff],
+    [0x00, 0xff, 0xff],
+    [0xff, 0xff, 0xff],
+];
+
+/// Channel levels of the 6x6x6 color cube (indices 16-231)
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The color of an xterm 256-color index, using xterm's defaults for 0-15
+pub fn xterm_rgb(index: u8) -> Rgb {
+    match index {
+        0..=15 => {
+            let [r, g, b] = XTERM_16[index as usize];
+            Rgb { r, g, b }
+        }
+        16..=231 => {
+            let cube = index - 16;
+            Rgb {
+                r: CUBE_LEVELS[(cube / 36) as usize],
+                g: CUBE_LEVELS[(cube / 6 % 6) as usize],
+                b: CUBE_LEVELS[(cube % 6) as usize],
+            }
+        }
+        232..=255 => {
+            let gray = 8 + (index - 232) * 10;
+            Rgb {
+                r: gray,
+                g: gray,
+                b: gray,
+            }
+        }
+    }
+}
+
+/// CIELAB values of all 256 xterm colors, computed once
+fn xterm_labs() -> &'static [Lab; 256] {
+    static LABS: OnceLock<[Lab; 256]> = OnceLock::new();
+    LABS.get_or_init(|| std::array::from_fn(|index| Lab::from_rgb(&xterm_rgb(index as u8))))
+}
+
+fn nearest_in(rgb: &Rgb, indices: std::ops::RangeInclusive<u8>) -> u8 {
+    let lab = Lab::from_rgb(rgb);
+    let labs = xterm_labs();
+    indices
+        .min_by(|&a, &b| {
+            delta_e_2000(&lab, &labs[a as usize]).total_cmp(&delta_e_2000(&lab, &labs[b as usize]))
+        })
+        .unwrap_or(0)
+}
+
+/// The xterm 256-color index that looks closest to a color
+///
+/// Only the color cube and the gray ramp (16-255) are considered, since
+/// terminals and themes usually redefine the first 16 colors.
+pub fn nearest_ansi256(rgb: &Rgb) -> u8 {
+    nearest_in(rgb, 16..=255)
+}
+
+/// The basic 16-color index that looks closest to a color, assuming xterm's defaults
+pub fn nearest_ansi16(rgb: &Rgb) -> u8 {
+    nearest_in(rgb, 0..=15)
+}
@@ -1,3 +1,5 @@
+use crate::scheme::{ContrastLevel, ContrastMetric, Scheme};
+use crate::theme::PaletteAdjustments;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
@@ -16,8 +18,52 @@ pub struct ConfigGroup {
     pub sections: HashMap<String, ConfigSection>,
 }
 
-// A representation of the entire config structure as a nested HashMap
-pub type Config = HashMap<String, HashMap<String, ConfigSection>>;
+// A representation of the entire config structure: palette settings at the
+// top level, followed by groups of sections as a nested HashMap
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Config {
+    /// Dynamic scheme variant used to generate the palette
+    #[serde(default)]
+    pub scheme: Option<Scheme>,
+
+    /// Contrast level: standard, medium, high or a number from -1.0 to 1.0
+    #[serde(default)]
+    pub contrast: Option<ContrastLevel>,
+
+    /// Contrast metric used when solving on-colors: wcag or apca
+    #[serde(default)]
+    pub contrast_metric: Option<ContrastMetric>,
+
+    /// Lift dark yellow-green source colors to a lighter tone (on by default)
+    #[serde(default)]
+    pub fix_disliked: Option<bool>,
+
+    /// Degrees added to the hue of every theme color
+    #[serde(default)]
+    pub hue_shift: Option<f64>,
+
+    /// Factor the chroma of every theme color is multiplied by
+    #[serde(default)]
+    pub chroma_scale: Option<f64>,
+
+    /// Tones added to the tone of every theme color
+    #[serde(default)]
+    pub tone_offset: Option<f64>,
+
+    #[serde(flatten)]
+    pub groups: HashMap<String, HashMap<String, ConfigSection>>,
+}
+
+impl Config {
+    /// The palette adjustments the config file sets
+    pub fn adjustments(&self) -> PaletteAdjustments {
+        PaletteAdjustments {
+            hue_shift: self.hue_shift,
+            chroma_scale: self.chroma_scale,
+            tone_offset: self.tone_offset,
+        }
+    }
+}
 
 pub fn resolve_path_to_abs(path: &str, base_dir: &str) -> Option<String> {
     if path.is_empty() {
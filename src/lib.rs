@@ -1,14 +1,25 @@
+pub mod ansi;
+pub mod base16;
+pub mod check;
 pub mod color;
 pub mod config;
 pub mod log;
 pub mod preview;
+pub mod pywal;
+pub mod quantize;
+pub mod scheme;
+pub mod score;
 pub mod theme;
+pub mod theme_file;
+pub mod wallpaper;
 
 /// Public API for tinct
 pub use color::*;
 pub use config::*;
 pub use log::*;
+pub use scheme::*;
 pub use theme::*;
+pub use wallpaper::*;
 
 /// Process a theme using a theme file, input template, and output path
 /// This is the main entry point for the library functionality
@@ -18,5 +29,11 @@ pub fn process_theme_workflow(
     output_path: &str,
     mode: &str,
 ) -> Result<(), String> {
-    theme::process_theme(theme_path, template_path, output_path, mode)
+    theme::process_theme(
+        theme_path,
+        template_path,
+        output_path,
+        mode,
+        &theme::PaletteOptions::default(),
+    )
 }
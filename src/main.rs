@@ -1,18 +1,12 @@
-use std::env;
-use std::fs;
 use std::path::Path;
 use std::process;
 
 mod cli;
-mod color;
-mod config;
-mod log;
-mod preview;
-mod theme;
 
 use clap::Parser;
 use colored::*;
-use config::Config;
+use tinct::theme_file::Theme;
+use tinct::{log, preview, theme, wallpaper};
 
 fn main() {
     let args = cli::CliArgs::parse();
@@ -41,81 +35,140 @@ fn main() {
     ) {
         println!("{}", "tinct - Theme Injector".bold());
         println!("{}: {}", "Config".blue(), config_path);
-        println!("{}: {}", "Theme".blue(), args.theme);
+        if let Some(theme) = &args.theme {
+            println!("{}: {}", "Theme".blue(), theme);
+        }
+        if let Some(image) = &args.image {
+            println!("{}: {}", "Image".blue(), image);
+        }
+        if let Some(color) = &args.color {
+            println!("{}: {}", "Color".blue(), color);
+        }
         println!("{}: {}", "Mode".blue(), args.mode.to_string().yellow());
+        if let Some(scheme) = args.scheme {
+            println!("{}: {}", "Scheme".blue(), scheme.to_string().yellow());
+        }
+        if let Some(contrast) = args.contrast {
+            println!("{}: {}", "Contrast".blue(), contrast.to_string().yellow());
+        }
+        if let Some(metric) = args.contrast_metric {
+            println!(
+                "{}: {}",
+                "Contrast metric".blue(),
+                metric.to_string().yellow()
+            );
+        }
         println!();
     }
 
-    // Resolve theme path - check both project themes and user themes in ~/.config/tinct/themes/
-    let theme_file = resolve_theme_path(&args.theme);
-
-    fn resolve_theme_path(theme_name: &str) -> String {
-        use std::env;
-
-        // First, check if the theme path is provided as an absolute path
-        if Path::new(theme_name).is_absolute() && Path::new(theme_name).exists() {
-            return theme_name.to_string();
+    // Load the theme, or build one from a source color or the wallpaper's source color
+    let theme_all = if let Some(image) = &args.image {
+        match wallpaper::source_hex_from_image(image) {
+            Ok(source_hex) => {
+                if matches!(args.log_level, cli::LogLevel::Verbose) {
+                    println!("Source color from image: {}", source_hex);
+                }
+                theme::seed_theme(&source_hex)
+            }
+            Err(e) => {
+                eprintln!("Error extracting color from image: {}", e);
+                process::exit(1);
+            }
         }
-
-        // Check if it's a relative path that exists from current directory
-        if Path::new(theme_name).exists() {
-            return Path::new(theme_name)
-                .canonicalize()
-                .unwrap_or_else(|_| std::path::PathBuf::from(theme_name))
-                .to_string_lossy()
-                .to_string();
+    } else if let Some(source_hex) = &args.color {
+        if let Err(e) = tinct::color::parse_color(source_hex) {
+            eprintln!("Invalid source color '{}': {}", source_hex, e);
+            process::exit(1);
+        }
+        theme::seed_theme(source_hex)
+    } else {
+        // Resolve theme path - check both project themes and user themes in ~/.config/tinct/themes/
+        let theme_name = args.theme.as_deref().unwrap_or_default();
+        let theme_file = match theme::resolve_theme_path(theme_name) {
+            Ok(theme_file) => theme_file,
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        };
+        match theme::load_theme(&theme_file) {
+            Ok(theme_all) => theme_all,
+            Err(e) => {
+                eprintln!("Error loading theme: {}", e);
+                process::exit(1);
+            }
         }
+    };
+    let theme_all = match Theme::from_value(&theme_all) {
+        Ok(theme_all) => theme_all,
+        Err(e) => {
+            eprintln!("Error loading theme: {}", e);
+            process::exit(1);
+        }
+    };
 
-        // Check in project's themes directory
-        let project_themes_path = Path::new(env!("CARGO_MANIFEST_DIR"))
-            .join("themes")
-            .join(format!("{}.json", theme_name));
-        if project_themes_path.exists() {
-            return project_themes_path.to_string_lossy().to_string();
+    // Preview, export and the pywal cache work without a config file, but
+    // palette settings are still taken from it when there is one
+    let config = match cli::load_optional_config(&config_path) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error loading config: {}", e);
+            process::exit(1);
         }
+    };
 
-        // Check in user's config directory ~/.config/tinct/themes/
-        if let Ok(home_dir) = env::var("HOME") {
-            let user_themes_path = Path::new(&home_dir)
-                .join(".config")
-                .join("tinct")
-                .join("themes")
-                .join(format!("{}.json", theme_name));
-            if user_themes_path.exists() {
-                return user_themes_path.to_string_lossy().to_string();
+    // If preview flag is set, show color preview and exit
+    if args.preview || args.simulate.is_some() {
+        let options = cli::palette_options(&args, config.as_ref());
+        let simulation = cli::cvd_simulation(&args);
+        match preview::show_theme_preview(
+            &theme_all,
+            &args.mode.to_string(),
+            &options,
+            simulation.as_ref(),
+        ) {
+            Ok(()) => process::exit(0),
+            Err(e) => {
+                eprintln!("Error showing color preview: {}", e);
+                process::exit(1);
             }
         }
-
-        // If theme is not found anywhere, exit with error
-        eprintln!(
-            "Theme '{}' not found in any of these locations:\n  - Current directory\n  - Project themes/ directory\n  - ~/.config/tinct/themes/",
-            theme_name
-        );
-        process::exit(1);
     }
 
-    // If preview flag is set, show color preview and exit (before trying to load config)
-    if args.preview {
-        match preview::show_color_preview(&theme_file, &args.mode.to_string()) {
-            Ok(()) => process::exit(0),
+    // Export a base16 scheme and write the pywal cache, then process
+    // templates if there is a config file
+    if let Some(output_path) = &args.export_base16 {
+        let options = cli::palette_options(&args, config.as_ref());
+        match cli::export_base16(&args, &theme_all, &options, output_path) {
+            Ok(()) => log::info::success("base16", &format!("exported to {}", output_path)),
             Err(e) => {
-                eprintln!("Error showing color preview: {}", e);
+                eprintln!("Error exporting base16 scheme: {}", e);
                 process::exit(1);
             }
         }
     }
 
-    // Check if the config file exists
-    if !Path::new(&config_path).exists() {
-        eprintln!("Config file '{}' does not exist.", config_path);
-        process::exit(1);
+    if let Some(cache_dir) = &args.pywal {
+        let options = cli::palette_options(&args, config.as_ref());
+        match cli::write_pywal_cache(&args, &theme_all, &options, cache_dir) {
+            Ok(()) => log::info::success("pywal", &format!("cache written to {}", cache_dir)),
+            Err(e) => {
+                eprintln!("Error writing pywal cache: {}", e);
+                process::exit(1);
+            }
+        }
     }
 
-    // Read TOML config
-    let config_content = fs::read_to_string(&config_path).expect("Could not read config file");
+    // Exports alone do not need a config file
+    if config.is_none() && (args.export_base16.is_some() || args.pywal.is_some()) {
+        process::exit(0);
+    }
 
-    let mut config: Config =
-        toml::from_str(&config_content).expect("Invalid TOML format in config file");
+    // Templates need a config file
+    let Some(mut config) = config else {
+        eprintln!("Config file '{}' does not exist.", config_path);
+        process::exit(1);
+    };
 
     // Convert relative paths in config to absolute paths
     // Paths should be resolved relative to the config file location, not the project root
@@ -125,7 +178,7 @@ fn main() {
         .to_string_lossy()
         .to_string();
 
-    for (_group_name, group) in config.iter_mut() {
+    for (_group_name, group) in config.groups.iter_mut() {
         for (_section_name, section) in group.iter_mut() {
             // Resolve input_path
             let expanded_input_path = shellexpand::tilde(&section.input_path).to_string();
@@ -177,7 +230,8 @@ fn main() {
     let mut total_count = 0;
 
     let mode_str = args.mode.to_string();
-    for (group_name, group) in config.iter() {
+    let options = cli::palette_options(&args, Some(&config));
+    for (group_name, group) in config.groups.iter() {
         if matches!(args.log_level, cli::LogLevel::Verbose) {
             println!("Processing group: {}", group_name);
         }
@@ -191,8 +245,9 @@ fn main() {
             let result = cli::process_section(
                 section_name,
                 section,
-                &theme_file,
+                &theme_all,
                 &mode_str,
+                &options,
                 args.log_level.clone(),
             );
 
@@ -205,9 +260,9 @@ fn main() {
                 cli::LogLevel::Normal | cli::LogLevel::Verbose
             ) {
                 if result {
-                    crate::log::info::processed_successfully(section_name);
+                    log::info::processed_successfully(section_name);
                 } else {
-                    crate::log::error::message(section_name, "failed to process");
+                    log::error::message(section_name, "failed to process");
                 }
             }
         }
@@ -218,6 +273,6 @@ fn main() {
         cli::LogLevel::Normal | cli::LogLevel::Verbose
     ) {
         println!();
-        crate::log::general::summary(success_count, total_count);
+        log::general::summary(success_count, total_count);
     }
 }
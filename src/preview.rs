@@ -1,32 +1,157 @@
-use crate::theme::{generate_palette, load_theme, select_theme_mode};
+use crate::check;
+use crate::color::CvdSimulation;
+use crate::scheme::ContrastMetric;
+use crate::theme::{generate_palette_with_options, load_theme, PaletteOptions};
+use crate::theme_file::Theme;
 use colored::*;
 
 /// Display a color preview showing all available colors in the theme as a matrix
-pub fn show_color_preview(theme_path: &str, mode: &str) -> Result<(), String> {
+pub fn show_color_preview(
+    theme_path: &str,
+    mode: &str,
+    options: &PaletteOptions,
+) -> Result<(), String> {
     // Load the theme
-    let theme_all = load_theme(theme_path)?;
-    let (theme, effective_mode) = select_theme_mode(&theme_all, mode)?;
+    let theme_all = Theme::from_value(&load_theme(theme_path)?)?;
+    show_theme_preview(&theme_all, mode, options, None)
+}
+
+/// Display a color preview for already loaded theme data
+///
+/// With a simulation, colors are shown as seen with that color vision
+/// deficiency, followed by the role pairs that become hard to tell apart.
+pub fn show_theme_preview(
+    theme_all: &Theme,
+    mode: &str,
+    options: &PaletteOptions,
+    simulation: Option<&CvdSimulation>,
+) -> Result<(), String> {
+    let (theme, effective_mode) = theme_all.mode(mode)?;
 
     // Generate palette
-    let palette = generate_palette(&theme, effective_mode == "dark", false)?;
+    let palette = generate_palette_with_options(&theme, effective_mode == "dark", false, options)?;
 
     println!(
         "{}",
         "🎨 Material Design 3 Color Preview".bold().underline()
     );
     println!("🌙 Theme Mode: {}", effective_mode.bold());
+    if let Some(scheme) = options.scheme {
+        println!("🎛  Scheme: {}", scheme.to_string().bold());
+    }
+    if let Some(simulation) = simulation {
+        println!(
+            "👁  Simulating: {} (severity {})",
+            simulation.deficiency.to_string().bold(),
+            simulation.severity
+        );
+    }
     println!();
 
     // Display colors in MD3 style similar to the official documentation
-    display_md3_cards_grid(&palette);
+    display_md3_cards_grid(&palette, simulation);
+    display_ansi_palette(&palette, simulation);
+    display_low_contrast_pairs(&palette, options.metric);
+
+    if let Some(simulation) = simulation {
+        display_collapsed_pairs(&palette, simulation);
+    }
 
     Ok(())
 }
 
+/// List the on-colors that fall below the minimum contrast under a metric
+fn display_low_contrast_pairs(palette: &crate::theme::Palette, metric: ContrastMetric) {
+    let format_contrast = |contrast: f64| match metric {
+        ContrastMetric::Wcag => format!("{:.2}:1", contrast),
+        ContrastMetric::Apca => format!("Lc {:.1}", contrast),
+    };
+    let low = check::low_contrast_pairs(palette, metric);
+    if low.is_empty() {
+        println!(
+            "{}",
+            format!("✓ All on-colors meet the minimum contrast ({})", metric).green()
+        );
+        return;
+    }
+
+    println!(
+        "{}",
+        format!("⚠ On-colors below the minimum contrast ({}):", metric)
+            .yellow()
+            .bold()
+    );
+    for pair in low {
+        println!(
+            "  {} on {}: {} (needs {})",
+            pair.foreground,
+            pair.background,
+            format_contrast(pair.contrast),
+            format_contrast(pair.target)
+        );
+    }
+}
+
+/// List the role pairs that look alike under a color vision deficiency simulation
+fn display_collapsed_pairs(palette: &crate::theme::Palette, simulation: &CvdSimulation) {
+    let collapsed =
+        check::collapsed_role_pairs(palette, simulation.deficiency, simulation.severity);
+    if collapsed.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "✓ All checked role pairs stay distinguishable ({})",
+                simulation.deficiency
+            )
+            .green()
+        );
+        return;
+    }
+
+    println!(
+        "{}",
+        format!("⚠ Role pairs that collapse ({}):", simulation.deficiency)
+            .yellow()
+            .bold()
+    );
+    for pair in collapsed {
+        println!(
+            "  {} / {}: ΔE {:.1} → {:.1}",
+            pair.first, pair.second, pair.delta_e, pair.simulated_delta_e
+        );
+    }
+}
+
 /// Display colors in a card grid layout similar to the MD3 official documentation
-fn display_md3_cards_grid(palette: &crate::theme::Palette) {
+fn display_md3_cards_grid(palette: &crate::theme::Palette, simulation: Option<&CvdSimulation>) {
+    // Labels of the custom color cards, e.g. "Git Added" and "On Git Added"
+    let custom_labels: Vec<[String; 4]> = palette
+        .custom
+        .iter()
+        .map(|custom| {
+            let name = custom
+                .name
+                .split('_')
+                .filter(|word| !word.is_empty())
+                .map(|word| {
+                    let mut chars = word.chars();
+                    chars.next().map_or(String::new(), |first| {
+                        first.to_uppercase().chain(chars).collect()
+                    })
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            [
+                name.clone(),
+                format!("On {}", name),
+                format!("{} Container", name),
+                format!("On {} Container", name),
+            ]
+        })
+        .collect();
+
     // Define color cards based on the MD3 documentation structure
-    let cards = vec![
+    let mut cards = vec![
         // Primary card
         vec![
             ("Primary", &palette.primary.default),
@@ -112,6 +237,18 @@ fn display_md3_cards_grid(palette: &crate::theme::Palette) {
         ],
     ];
 
+    // Custom color cards, laid out like the accent cards
+    for (custom, labels) in palette.custom.iter().zip(&custom_labels) {
+        let roles = custom.roles();
+        cards.push(
+            labels
+                .iter()
+                .zip(roles)
+                .map(|(label, (_, color))| (label.as_str(), color))
+                .collect(),
+        );
+    }
+
     // Print cards in groups of 3 per row
     const CARDS_PER_ROW: usize = 3;
 
@@ -127,6 +264,10 @@ fn display_md3_cards_grid(palette: &crate::theme::Palette) {
                 for (idx, card) in chunk.iter().enumerate() {
                     if color_idx < card.len() {
                         let (label, color) = &card[color_idx];
+                        let color = match simulation {
+                            Some(simulation) => simulation.apply(&color.rgb()),
+                            None => color.rgb(),
+                        };
 
                         // Create a color block with centered text
                         let block_width = 24; // Increased width to accommodate longer text
@@ -169,13 +310,12 @@ fn display_md3_cards_grid(palette: &crate::theme::Palette) {
                         };
 
                         // Apply the background color to the content
-                        let color_block =
-                            display_content.on_truecolor(color.red, color.green, color.blue);
+                        let color_block = display_content.on_truecolor(color.r, color.g, color.b);
 
                         // Choose text color based on contrast
-                        let text_color = if (0.299 * color.red as f64
-                            + 0.587 * color.green as f64
-                            + 0.114 * color.blue as f64)
+                        let text_color = if (0.299 * color.r as f64
+                            + 0.587 * color.g as f64
+                            + 0.114 * color.b as f64)
                             > 128.0
                         {
                             // Dark text for light backgrounds
@@ -203,3 +343,28 @@ fn display_md3_cards_grid(palette: &crate::theme::Palette) {
         println!(); // Extra spacing between rows of cards
     }
 }
+
+/// Display the 16 ANSI terminal colors as two rows of swatches, normal then bright
+fn display_ansi_palette(palette: &crate::theme::Palette, simulation: Option<&CvdSimulation>) {
+    println!("{}", "ANSI Colors".bold());
+    for (row, colors) in palette.ansi.colors().chunks(8).enumerate() {
+        for (idx, color) in colors.iter().enumerate() {
+            let color = match simulation {
+                Some(simulation) => simulation.apply(&color.rgb()),
+                None => color.rgb(),
+            };
+            let label = format!("{:^6}", row * 8 + idx).on_truecolor(color.r, color.g, color.b);
+            // Same luminance rule as the cards
+            let label = if 0.299 * color.r as f64 + 0.587 * color.g as f64 + 0.114 * color.b as f64
+                > 128.0
+            {
+                label.black()
+            } else {
+                label.white()
+            };
+            print!(" {}", label);
+        }
+        println!();
+    }
+    println!();
+}
@@ -0,0 +1,198 @@
+// pywal interoperability
+// Writes the `colors.json` and `colors.sh` cache files that tools built around
+// pywal read from `~/.cache/wal`, and reads a pywal `colors.json` as a theme
+
+use crate::ansi::ANSI_NAMES;
+use crate::color;
+use crate::theme::Palette;
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::Path;
+
+/// Directory pywal keeps its cache files in
+pub const DEFAULT_CACHE_DIR: &str = "~/.cache/wal";
+
+/// The colors of a pywal cache: the special colors and color0 to color15
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PywalColors {
+    pub background: String,
+    pub foreground: String,
+    pub cursor: String,
+    /// color0 to color15
+    pub colors: Vec<String>,
+}
+
+/// Serializes `(key, value)` pairs as an object, keeping their order
+struct Ordered<'a>(Vec<(String, &'a str)>);
+
+impl Serialize for Ordered<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (key, value) in &self.0 {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+#[derive(serde::Serialize)]
+struct ColorsFile<'a> {
+    wallpaper: &'a str,
+    alpha: &'a str,
+    special: Ordered<'a>,
+    colors: Ordered<'a>,
+}
+
+impl PywalColors {
+    /// Take the pywal colors from a palette
+    ///
+    /// The background and foreground are the surface and on-surface colors,
+    /// the cursor is the foreground as in pywal, and color0 to color15 are the
+    /// palette's ANSI colors.
+    pub fn from_palette(palette: &Palette) -> Result<PywalColors, String> {
+        let colors = (0..ANSI_NAMES.len())
+            .map(|index| {
+                palette
+                    .ansi
+                    .get(&format!("color{}", index))
+                    .map(|color| color.hex.clone())
+                    .ok_or_else(|| format!("Palette has no ANSI color{}", index))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(PywalColors {
+            background: palette.surface.default.hex.clone(),
+            foreground: palette.on_surface.default.hex.clone(),
+            cursor: palette.on_surface.default.hex.clone(),
+            colors,
+        })
+    }
+
+    fn special(&self) -> [(&'static str, &str); 3] {
+        [
+            ("background", &self.background),
+            ("foreground", &self.foreground),
+            ("cursor", &self.cursor),
+        ]
+    }
+
+    /// The cache's `colors.json`
+    pub fn to_json(&self, wallpaper: &str) -> Result<String, String> {
+        let file = ColorsFile {
+            wallpaper,
+            alpha: "100",
+            special: Ordered(
+                self.special()
+                    .into_iter()
+                    .map(|(key, value)| (key.to_string(), value))
+                    .collect(),
+            ),
+            colors: Ordered(
+                self.colors
+                    .iter()
+                    .enumerate()
+                    .map(|(index, value)| (format!("color{}", index), value.as_str()))
+                    .collect(),
+            ),
+        };
+        serde_json::to_string_pretty(&file)
+            .map(|json| json + "\n")
+            .map_err(|e| format!("Could not write colors.json: {}", e))
+    }
+
+    /// The cache's `colors.sh`, shell variables for scripts to source
+    pub fn to_shell(&self, wallpaper: &str) -> String {
+        let mut script = String::from("# Shell variables\n# Generated by tinct\n");
+        script.push_str(&format!(
+            "wallpaper={}\n\n# Special\n",
+            shell_quote(wallpaper)
+        ));
+        for (key, value) in self.special() {
+            script.push_str(&format!("{}={}\n", key, shell_quote(value)));
+        }
+        script.push_str("\n# Colors\n");
+        for (index, value) in self.colors.iter().enumerate() {
+            script.push_str(&format!("color{}={}\n", index, shell_quote(value)));
+        }
+        script
+    }
+
+    /// Write `colors.json` and `colors.sh` to a cache directory, creating it if needed
+    pub fn write_cache(&self, cache_dir: &Path, wallpaper: &str) -> Result<(), String> {
+        fs::create_dir_all(cache_dir)
+            .map_err(|e| format!("Could not create '{}': {}", cache_dir.display(), e))?;
+        for (name, content) in [
+            ("colors.json", self.to_json(wallpaper)?),
+            ("colors.sh", self.to_shell(wallpaper)),
+        ] {
+            let path = cache_dir.join(name);
+            fs::write(&path, content)
+                .map_err(|e| format!("Could not write '{}': {}", path.display(), e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Quote a value for a shell script, so that sourcing it never runs any of it
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Whether theme data is a pywal `colors.json` rather than a tinct theme
+pub fn is_pywal_colors(data: &Value) -> bool {
+    data.get("special").is_some_and(Value::is_object)
+        && data
+            .get("colors")
+            .and_then(|colors| colors.get("color0"))
+            .is_some()
+}
+
+/// Convert a pywal `colors.json` into tinct theme data
+///
+/// The colors fill the mode matching the tone of the background. The
+/// background and foreground become the surface and on-surface colors,
+/// color1, color2 and color3 (the wallpaper's most prominent colors) the
+/// primary, secondary and tertiary colors, color8 the outline, and color0 to
+/// color15 the ANSI colors.
+pub fn theme_from_pywal(data: &Value) -> Result<Value, String> {
+    let color = |group: &str, name: &str| {
+        let value = data
+            .get(group)
+            .and_then(|colors| colors.get(name))
+            .ok_or_else(|| format!("{}.{} is missing", group, name))?;
+        let text = value
+            .as_str()
+            .ok_or_else(|| format!("{}.{}: expected a color, got {}", group, name, value))?;
+        let rgb = color::parse_color(text)
+            .map_err(|e| format!("{}.{}: {}", group, name, e))?
+            .rgb();
+        Ok::<_, String>(color::rgb_to_hex(rgb.r as f64, rgb.g as f64, rgb.b as f64))
+    };
+
+    let background = color("special", "background")?;
+    let foreground = color("special", "foreground")?;
+    let mut ansi = Map::new();
+    for index in 0..ANSI_NAMES.len() {
+        let name = format!("color{}", index);
+        ansi.insert(name.clone(), Value::String(color("colors", &name)?));
+    }
+
+    let rgb = color::hex_to_rgb(&background)?;
+    let variant = if color::rgb_to_hct(rgb.r, rgb.g, rgb.b).t < 50.0 {
+        "dark"
+    } else {
+        "light"
+    };
+    let mode = serde_json::json!({
+        "primary": color("colors", "color1")?,
+        "secondary": color("colors", "color2")?,
+        "tertiary": color("colors", "color3")?,
+        "surface": background,
+        "background": background,
+        "on_surface": foreground,
+        "on_background": foreground,
+        "outline": color("colors", "color8")?,
+        "ansi": ansi,
+    });
+    Ok(serde_json::json!({ variant: mode }))
+}
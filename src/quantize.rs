@@ -0,0 +1,495 @@
+// Color quantization, as used by Material Design 3 to find the dominant colors of an image
+// Wu's quantizer produces the starting clusters, which weighted k-means then refines
+
+use crate::color::{self, Lab, Rgb};
+use std::collections::HashMap;
+
+/// Reduce a list of pixels to at most `max_colors` colors, with the number of pixels for each
+pub fn quantize(pixels: &[Rgb], max_colors: usize) -> HashMap<Rgb, u32> {
+    let wu = quantize_wu(pixels, max_colors);
+    quantize_wsmeans(pixels, &wu, max_colors)
+}
+
+/// Count how many times each distinct color occurs
+fn histogram(pixels: &[Rgb]) -> HashMap<Rgb, u32> {
+    let mut counts = HashMap::new();
+    for pixel in pixels {
+        *counts.entry(*pixel).or_insert(0) += 1;
+    }
+    counts
+}
+
+// Wu's color quantizer
+// Splits the RGB cube into boxes along the axis that minimizes variance, using
+// cumulative moments over a 32x32x32 histogram so each box is evaluated in constant time.
+
+const INDEX_BITS: u32 = 5;
+const SIDE_LENGTH: usize = 33; // (1 << INDEX_BITS) + 1
+const TOTAL_SIZE: usize = SIDE_LENGTH * SIDE_LENGTH * SIDE_LENGTH;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Direction {
+    Red,
+    Green,
+    Blue,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Cube {
+    r0: usize,
+    r1: usize,
+    g0: usize,
+    g1: usize,
+    b0: usize,
+    b1: usize,
+    vol: usize,
+}
+
+fn index(r: usize, g: usize, b: usize) -> usize {
+    r * SIDE_LENGTH * SIDE_LENGTH + g * SIDE_LENGTH + b
+}
+
+struct Moments {
+    weights: Vec<f64>,
+    r: Vec<f64>,
+    g: Vec<f64>,
+    b: Vec<f64>,
+    squares: Vec<f64>,
+}
+
+impl Moments {
+    fn new(pixels: &[Rgb]) -> Self {
+        let mut moments = Self {
+            weights: vec![0.0; TOTAL_SIZE],
+            r: vec![0.0; TOTAL_SIZE],
+            g: vec![0.0; TOTAL_SIZE],
+            b: vec![0.0; TOTAL_SIZE],
+            squares: vec![0.0; TOTAL_SIZE],
+        };
+
+        let bits_to_remove = 8 - INDEX_BITS;
+        for (pixel, count) in histogram(pixels) {
+            let (red, green, blue) = (pixel.r as f64, pixel.g as f64, pixel.b as f64);
+            let i = index(
+                (pixel.r >> bits_to_remove) as usize + 1,
+                (pixel.g >> bits_to_remove) as usize + 1,
+                (pixel.b >> bits_to_remove) as usize + 1,
+            );
+            let count = count as f64;
+            moments.weights[i] += count;
+            moments.r[i] += count * red;
+            moments.g[i] += count * green;
+            moments.b[i] += count * blue;
+            moments.squares[i] += count * (red * red + green * green + blue * blue);
+        }
+
+        moments.accumulate();
+        moments
+    }
+
+    /// Turn the histogram into cumulative moments
+    fn accumulate(&mut self) {
+        for r in 1..SIDE_LENGTH {
+            let mut area = [0.0; SIDE_LENGTH];
+            let mut area_r = [0.0; SIDE_LENGTH];
+            let mut area_g = [0.0; SIDE_LENGTH];
+            let mut area_b = [0.0; SIDE_LENGTH];
+            let mut area_squares = [0.0; SIDE_LENGTH];
+            for g in 1..SIDE_LENGTH {
+                let mut line = 0.0;
+                let mut line_r = 0.0;
+                let mut line_g = 0.0;
+                let mut line_b = 0.0;
+                let mut line_squares = 0.0;
+                for b in 1..SIDE_LENGTH {
+                    let i = index(r, g, b);
+                    line += self.weights[i];
+                    line_r += self.r[i];
+                    line_g += self.g[i];
+                    line_b += self.b[i];
+                    line_squares += self.squares[i];
+
+                    area[b] += line;
+                    area_r[b] += line_r;
+                    area_g[b] += line_g;
+                    area_b[b] += line_b;
+                    area_squares[b] += line_squares;
+
+                    let previous = index(r - 1, g, b);
+                    self.weights[i] = self.weights[previous] + area[b];
+                    self.r[i] = self.r[previous] + area_r[b];
+                    self.g[i] = self.g[previous] + area_g[b];
+                    self.b[i] = self.b[previous] + area_b[b];
+                    self.squares[i] = self.squares[previous] + area_squares[b];
+                }
+            }
+        }
+    }
+}
+
+fn volume(cube: &Cube, moment: &[f64]) -> f64 {
+    moment[index(cube.r1, cube.g1, cube.b1)]
+        - moment[index(cube.r1, cube.g1, cube.b0)]
+        - moment[index(cube.r1, cube.g0, cube.b1)]
+        + moment[index(cube.r1, cube.g0, cube.b0)]
+        - moment[index(cube.r0, cube.g1, cube.b1)]
+        + moment[index(cube.r0, cube.g1, cube.b0)]
+        + moment[index(cube.r0, cube.g0, cube.b1)]
+        - moment[index(cube.r0, cube.g0, cube.b0)]
+}
+
+fn bottom(cube: &Cube, direction: Direction, moment: &[f64]) -> f64 {
+    match direction {
+        Direction::Red => {
+            -moment[index(cube.r0, cube.g1, cube.b1)]
+                + moment[index(cube.r0, cube.g1, cube.b0)]
+                + moment[index(cube.r0, cube.g0, cube.b1)]
+                - moment[index(cube.r0, cube.g0, cube.b0)]
+        }
+        Direction::Green => {
+            -moment[index(cube.r1, cube.g0, cube.b1)]
+                + moment[index(cube.r1, cube.g0, cube.b0)]
+                + moment[index(cube.r0, cube.g0, cube.b1)]
+                - moment[index(cube.r0, cube.g0, cube.b0)]
+        }
+        Direction::Blue => {
+            -moment[index(cube.r1, cube.g1, cube.b0)]
+                + moment[index(cube.r1, cube.g0, cube.b0)]
+                + moment[index(cube.r0, cube.g1, cube.b0)]
+                - moment[index(cube.r0, cube.g0, cube.b0)]
+        }
+    }
+}
+
+fn top(cube: &Cube, direction: Direction, position: usize, moment: &[f64]) -> f64 {
+    match direction {
+        Direction::Red => {
+            moment[index(position, cube.g1, cube.b1)]
+                - moment[index(position, cube.g1, cube.b0)]
+                - moment[index(position, cube.g0, cube.b1)]
+                + moment[index(position, cube.g0, cube.b0)]
+        }
+        Direction::Green => {
+            moment[index(cube.r1, position, cube.b1)]
+                - moment[index(cube.r1, position, cube.b0)]
+                - moment[index(cube.r0, position, cube.b1)]
+                + moment[index(cube.r0, position, cube.b0)]
+        }
+        Direction::Blue => {
+            moment[index(cube.r1, cube.g1, position)]
+                - moment[index(cube.r1, cube.g0, position)]
+                - moment[index(cube.r0, cube.g1, position)]
+                + moment[index(cube.r0, cube.g0, position)]
+        }
+    }
+}
+
+fn variance(cube: &Cube, moments: &Moments) -> f64 {
+    let dr = volume(cube, &moments.r);
+    let dg = volume(cube, &moments.g);
+    let db = volume(cube, &moments.b);
+    let xx = volume(cube, &moments.squares);
+    let hypotenuse = dr * dr + dg * dg + db * db;
+    xx - hypotenuse / volume(cube, &moments.weights)
+}
+
+/// The best place to cut a cube along one axis, and how good that cut is
+fn maximize(
+    cube: &Cube,
+    direction: Direction,
+    first: usize,
+    last: usize,
+    whole: [f64; 4],
+    moments: &Moments,
+) -> (Option<usize>, f64) {
+    let bottom_r = bottom(cube, direction, &moments.r);
+    let bottom_g = bottom(cube, direction, &moments.g);
+    let bottom_b = bottom(cube, direction, &moments.b);
+    let bottom_w = bottom(cube, direction, &moments.weights);
+
+    let mut max = 0.0;
+    let mut cut = None;
+    for i in first..last {
+        let mut half_r = bottom_r + top(cube, direction, i, &moments.r);
+        let mut half_g = bottom_g + top(cube, direction, i, &moments.g);
+        let mut half_b = bottom_b + top(cube, direction, i, &moments.b);
+        let mut half_w = bottom_w + top(cube, direction, i, &moments.weights);
+        if half_w == 0.0 {
+            continue;
+        }
+        let mut temp = (half_r * half_r + half_g * half_g + half_b * half_b) / half_w;
+
+        half_r = whole[0] - half_r;
+        half_g = whole[1] - half_g;
+        half_b = whole[2] - half_b;
+        half_w = whole[3] - half_w;
+        if half_w == 0.0 {
+            continue;
+        }
+        temp += (half_r * half_r + half_g * half_g + half_b * half_b) / half_w;
+
+        if temp > max {
+            max = temp;
+            cut = Some(i);
+        }
+    }
+    (cut, max)
+}
+
+/// Split `one` in two, storing the second half in `two`
+fn cut(one: &mut Cube, two: &mut Cube, moments: &Moments) -> bool {
+    let whole = [
+        volume(one, &moments.r),
+        volume(one, &moments.g),
+        volume(one, &moments.b),
+        volume(one, &moments.weights),
+    ];
+    let (cut_r, max_r) = maximize(one, Direction::Red, one.r0 + 1, one.r1, whole, moments);
+    let (cut_g, max_g) = maximize(one, Direction::Green, one.g0 + 1, one.g1, whole, moments);
+    let (cut_b, max_b) = maximize(one, Direction::Blue, one.b0 + 1, one.b1, whole, moments);
+
+    let (direction, location) = if max_r >= max_g && max_r >= max_b {
+        match cut_r {
+            Some(location) => (Direction::Red, location),
+            None => return false,
+        }
+    } else if max_g >= max_r && max_g >= max_b {
+        (Direction::Green, cut_g.unwrap_or(one.g0))
+    } else {
+        (Direction::Blue, cut_b.unwrap_or(one.b0))
+    };
+
+    two.r1 = one.r1;
+    two.g1 = one.g1;
+    two.b1 = one.b1;
+    match direction {
+        Direction::Red => {
+            one.r1 = location;
+            two.r0 = one.r1;
+            two.g0 = one.g0;
+            two.b0 = one.b0;
+        }
+        Direction::Green => {
+            one.g1 = location;
+            two.r0 = one.r0;
+            two.g0 = one.g1;
+            two.b0 = one.b0;
+        }
+        Direction::Blue => {
+            one.b1 = location;
+            two.r0 = one.r0;
+            two.g0 = one.g0;
+            two.b0 = one.b1;
+        }
+    }
+
+    one.vol = (one.r1 - one.r0) * (one.g1 - one.g0) * (one.b1 - one.b0);
+    two.vol = (two.r1 - two.r0) * (two.g1 - two.g0) * (two.b1 - two.b0);
+    true
+}
+
+/// Wu's quantizer: split the RGB cube into at most `max_colors` boxes and return their mean colors
+pub fn quantize_wu(pixels: &[Rgb], max_colors: usize) -> Vec<Rgb> {
+    if pixels.is_empty() || max_colors == 0 {
+        return Vec::new();
+    }
+    let moments = Moments::new(pixels);
+
+    let mut cubes = vec![Cube::default(); max_colors];
+    cubes[0].r1 = SIDE_LENGTH - 1;
+    cubes[0].g1 = SIDE_LENGTH - 1;
+    cubes[0].b1 = SIDE_LENGTH - 1;
+
+    let mut volume_variance = vec![0.0; max_colors];
+    let mut generated_count = max_colors;
+    let mut next = 0;
+    let mut i = 1;
+    while i < max_colors {
+        let (head, tail) = cubes.split_at_mut(i);
+        if cut(&mut head[next], &mut tail[0], &moments) {
+            volume_variance[next] = if head[next].vol > 1 {
+                variance(&head[next], &moments)
+            } else {
+                0.0
+            };
+            volume_variance[i] = if tail[0].vol > 1 {
+                variance(&tail[0], &moments)
+            } else {
+                0.0
+            };
+        } else {
+            volume_variance[next] = 0.0;
+            i -= 1;
+        }
+
+        next = 0;
+        let mut temp = volume_variance[0];
+        for (j, variance) in volume_variance.iter().enumerate().take(i + 1).skip(1) {
+            if *variance > temp {
+                temp = *variance;
+                next = j;
+            }
+        }
+        if temp <= 0.0 {
+            generated_count = i + 1;
+            break;
+        }
+        i += 1;
+    }
+
+    cubes
+        .iter()
+        .take(generated_count)
+        .filter_map(|cube| {
+            let weight = volume(cube, &moments.weights);
+            if weight <= 0.0 {
+                return None;
+            }
+            let channel = |moment: &[f64]| {
+                color::clamp((volume(cube, moment) / weight).round(), 0.0, 255.0) as u8
+            };
+            Some(Rgb {
+                r: channel(&moments.r),
+                g: channel(&moments.g),
+                b: channel(&moments.b),
+            })
+        })
+        .collect()
+}
+
+// Weighted k-means quantizer
+// Clusters distinct colors in L*a*b*, weighting each by its pixel count, and
+// skips distance checks the triangle inequality proves unnecessary.
+
+const MAX_ITERATIONS: usize = 10;
+const MIN_MOVEMENT_DISTANCE: f64 = 3.0;
+
+fn to_lab(rgb: &Rgb) -> [f64; 3] {
+    let lab = Lab::from_rgb(rgb);
+    [lab.l, lab.a, lab.b]
+}
+
+fn from_lab(&[l, a, b]: &[f64; 3]) -> Rgb {
+    Lab { l, a, b }.to_rgb()
+}
+
+fn lab_distance(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    let dl = a[0] - b[0];
+    let da = a[1] - b[1];
+    let db = a[2] - b[2];
+    dl * dl + da * da + db * db
+}
+
+/// Weighted k-means, starting from the given clusters; returns each cluster's color and pixel count
+pub fn quantize_wsmeans(
+    pixels: &[Rgb],
+    starting_clusters: &[Rgb],
+    max_colors: usize,
+) -> HashMap<Rgb, u32> {
+    let counts_by_color = histogram(pixels);
+    let mut colors: Vec<Rgb> = counts_by_color.keys().copied().collect();
+    // Sort so results don't depend on hash map iteration order
+    colors.sort_by_key(|c| (c.r, c.g, c.b));
+    let points: Vec<[f64; 3]> = colors.iter().map(to_lab).collect();
+    let counts: Vec<u32> = colors.iter().map(|c| counts_by_color[c]).collect();
+
+    let mut cluster_count = max_colors.min(points.len());
+    if !starting_clusters.is_empty() {
+        cluster_count = cluster_count.min(starting_clusters.len());
+    }
+    if cluster_count == 0 {
+        return HashMap::new();
+    }
+
+    let mut clusters: Vec<[f64; 3]> = starting_clusters
+        .iter()
+        .take(cluster_count)
+        .map(to_lab)
+        .collect();
+    // Without starting clusters, seed from evenly spaced points
+    let step = (points.len() / cluster_count).max(1);
+    while clusters.len() < cluster_count {
+        clusters.push(points[(clusters.len() * step) % points.len()]);
+    }
+
+    let mut cluster_indices: Vec<usize> = (0..points.len()).map(|i| i % cluster_count).collect();
+    let mut distance_to_index = vec![vec![(0.0, 0usize); cluster_count]; cluster_count];
+    let mut pixel_count_sums = vec![0u32; cluster_count];
+
+    for iteration in 0..MAX_ITERATIONS {
+        for i in 0..cluster_count {
+            distance_to_index[i][i] = (0.0, i);
+            for j in (i + 1)..cluster_count {
+                let distance = lab_distance(&clusters[i], &clusters[j]);
+                distance_to_index[j][i] = (distance, i);
+                distance_to_index[i][j] = (distance, j);
+            }
+        }
+        let sorted_neighbors: Vec<Vec<(f64, usize)>> = distance_to_index
+            .iter()
+            .map(|row| {
+                let mut row = row.clone();
+                row.sort_by(|a, b| a.0.total_cmp(&b.0));
+                row
+            })
+            .collect();
+
+        let mut points_moved = 0;
+        for (i, point) in points.iter().enumerate() {
+            let previous_cluster_index = cluster_indices[i];
+            let previous_distance = lab_distance(point, &clusters[previous_cluster_index]);
+            let mut minimum_distance = previous_distance;
+            let mut new_cluster_index = None;
+            for &(cluster_distance, j) in &sorted_neighbors[previous_cluster_index] {
+                // Clusters this far away can't be closer than the current one
+                if cluster_distance >= 4.0 * previous_distance {
+                    break;
+                }
+                let distance = lab_distance(point, &clusters[j]);
+                if distance < minimum_distance {
+                    minimum_distance = distance;
+                    new_cluster_index = Some(j);
+                }
+            }
+            if let Some(new_index) = new_cluster_index {
+                let distance_change = (minimum_distance.sqrt() - previous_distance.sqrt()).abs();
+                if distance_change > MIN_MOVEMENT_DISTANCE {
+                    points_moved += 1;
+                    cluster_indices[i] = new_index;
+                }
+            }
+        }
+
+        if points_moved == 0 && iteration != 0 {
+            break;
+        }
+
+        let mut sums = vec![[0.0; 3]; cluster_count];
+        pixel_count_sums.fill(0);
+        for (i, point) in points.iter().enumerate() {
+            let cluster = cluster_indices[i];
+            let count = counts[i];
+            pixel_count_sums[cluster] += count;
+            for k in 0..3 {
+                sums[cluster][k] += point[k] * count as f64;
+            }
+        }
+        for (i, cluster) in clusters.iter_mut().enumerate() {
+            let count = pixel_count_sums[i] as f64;
+            *cluster = if count == 0.0 {
+                [0.0; 3]
+            } else {
+                [sums[i][0] / count, sums[i][1] / count, sums[i][2] / count]
+            };
+        }
+    }
+
+    let mut populations = HashMap::new();
+    for (cluster, &count) in clusters.iter().zip(&pixel_count_sums) {
+        if count == 0 {
+            continue;
+        }
+        populations.entry(from_lab(cluster)).or_insert(count);
+    }
+    populations
+}
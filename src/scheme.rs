@@ -0,0 +1,514 @@
+use crate::color::{self, Hct, TemperatureCache, TonalPalette};
+use crate::theme::TonalPalettes;
+use serde::{Deserialize, Serialize};
+
+/// Material Design 3 dynamic color scheme variants
+///
+/// Each variant derives the tonal palettes of a scheme from a single source
+/// color, using its own hue rotations and chroma rules.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum Scheme {
+    /// Calm, low-chroma scheme with a slightly rotated tertiary (MD3 default)
+    TonalSpot,
+    /// Maximum chroma primary with rotated secondary and tertiary
+    Vibrant,
+    /// Playful scheme whose primary hue is rotated away from the source
+    Expressive,
+    /// Primary stays faithful to the source color's chroma
+    Fidelity,
+    /// Like fidelity, with an analogous tertiary
+    Content,
+    /// Grayscale
+    Monochrome,
+    /// Nearly grayscale, with a hint of the source hue
+    Neutral,
+    /// Colorful accents with grayscale neutrals
+    Rainbow,
+    /// Accents rotated away from the source, tertiary keeps the source hue
+    FruitSalad,
+}
+
+impl std::fmt::Display for Scheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Scheme::TonalSpot => "tonal-spot",
+            Scheme::Vibrant => "vibrant",
+            Scheme::Expressive => "expressive",
+            Scheme::Fidelity => "fidelity",
+            Scheme::Content => "content",
+            Scheme::Monochrome => "monochrome",
+            Scheme::Neutral => "neutral",
+            Scheme::Rainbow => "rainbow",
+            Scheme::FruitSalad => "fruit-salad",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Scheme {
+    /// Derive the tonal palettes of this scheme variant from a source color
+    pub fn tonal_palettes(&self, source: &Hct) -> TonalPalettes {
+        let hue = source.h;
+        let chroma = source.c;
+        let palette = TonalPalette::from_hue_and_chroma;
+        let from_hct = |hct: &Hct| palette(hct.h, hct.c);
+        let rotated = |degrees: f64| color::sanitize_degrees(hue + degrees);
+
+        let (primary, secondary, tertiary, neutral, neutral_variant) = match self {
+            Scheme::TonalSpot => (
+                palette(hue, 36.0),
+                palette(hue, 16.0),
+                palette(rotated(60.0), 24.0),
+                palette(hue, 6.0),
+                palette(hue, 8.0),
+            ),
+            Scheme::Vibrant => {
+                const HUES: [f64; 9] = [0.0, 41.0, 61.0, 101.0, 131.0, 181.0, 251.0, 301.0, 360.0];
+                const SECONDARY: [f64; 9] = [18.0, 15.0, 10.0, 12.0, 15.0, 18.0, 15.0, 12.0, 12.0];
+                const TERTIARY: [f64; 9] = [35.0, 30.0, 20.0, 25.0, 30.0, 35.0, 30.0, 25.0, 25.0];
+                (
+                    palette(hue, 200.0),
+                    palette(rotated_hue(hue, &HUES, &SECONDARY), 24.0),
+                    palette(rotated_hue(hue, &HUES, &TERTIARY), 32.0),
+                    palette(hue, 10.0),
+                    palette(hue, 12.0),
+                )
+            }
+            Scheme::Expressive => {
+                const HUES: [f64; 9] = [0.0, 21.0, 51.0, 121.0, 151.0, 191.0, 271.0, 321.0, 360.0];
+                const SECONDARY: [f64; 9] = [45.0, 95.0, 45.0, 20.0, 45.0, 90.0, 45.0, 45.0, 45.0];
+                const TERTIARY: [f64; 9] =
+                    [120.0, 120.0, 20.0, 45.0, 20.0, 15.0, 20.0, 120.0, 120.0];
+                (
+                    palette(rotated(240.0), 40.0),
+                    palette(rotated_hue(hue, &HUES, &SECONDARY), 24.0),
+                    palette(rotated_hue(hue, &HUES, &TERTIARY), 32.0),
+                    palette(rotated(15.0), 8.0),
+                    palette(rotated(15.0), 12.0),
+                )
+            }
+            Scheme::Fidelity => (
+                palette(hue, chroma),
+                palette(hue, (chroma - 32.0).max(chroma * 0.5)),
+                from_hct(&color::fix_if_disliked(
+                    &TemperatureCache::new(source).complement(),
+                )),
+                palette(hue, chroma / 8.0),
+                palette(hue, chroma / 8.0 + 4.0),
+            ),
+            Scheme::Content => (
+                palette(hue, chroma),
+                palette(hue, (chroma - 32.0).max(chroma * 0.5)),
+                from_hct(&color::fix_if_disliked(
+                    &TemperatureCache::new(source).analogous_colors(3, 6)[2],
+                )),
+                palette(hue, chroma / 8.0),
+                palette(hue, chroma / 8.0 + 4.0),
+            ),
+            Scheme::Monochrome => (
+                palette(hue, 0.0),
+                palette(hue, 0.0),
+                palette(hue, 0.0),
+                palette(hue, 0.0),
+                palette(hue, 0.0),
+            ),
+            Scheme::Neutral => (
+                palette(hue, 12.0),
+                palette(hue, 8.0),
+                palette(rotated(60.0), 16.0),
+                palette(hue, 2.0),
+                palette(hue, 2.0),
+            ),
+            Scheme::Rainbow => (
+                palette(hue, 48.0),
+                palette(hue, 16.0),
+                palette(rotated(60.0), 24.0),
+                palette(hue, 0.0),
+                palette(hue, 0.0),
+            ),
+            Scheme::FruitSalad => (
+                palette(rotated(-50.0), 48.0),
+                palette(rotated(-50.0), 36.0),
+                palette(hue, 36.0),
+                palette(hue, 10.0),
+                palette(hue, 16.0),
+            ),
+        };
+
+        TonalPalettes {
+            primary,
+            secondary,
+            tertiary,
+            neutral,
+            neutral_variant,
+            error: palette(25.0, 84.0),
+        }
+    }
+}
+
+/// Rotate a hue by the amount given for the hue range it falls in
+///
+/// `hues` are the range boundaries and `rotations[i]` applies to hues between
+/// `hues[i]` and `hues[i + 1]`.
+fn rotated_hue(source_hue: f64, hues: &[f64], rotations: &[f64]) -> f64 {
+    for (i, range) in hues.windows(2).enumerate() {
+        if range[0] < source_hue && source_hue < range[1] {
+            return color::sanitize_degrees(source_hue + rotations[i]);
+        }
+    }
+    // Exactly on a boundary, no rotation
+    source_hue
+}
+
+/// Contrast level of a scheme, from -1.0 (reduced) through 0.0 (standard) to 1.0 (high)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(try_from = "ContrastSetting", into = "f64")]
+pub struct ContrastLevel(f64);
+
+/// A contrast level as written in the config file: a name or a number
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ContrastSetting {
+    Level(f64),
+    Name(String),
+}
+
+impl ContrastLevel {
+    pub const STANDARD: ContrastLevel = ContrastLevel(0.0);
+    pub const MEDIUM: ContrastLevel = ContrastLevel(0.5);
+    pub const HIGH: ContrastLevel = ContrastLevel(1.0);
+
+    /// Create a contrast level, which must be between -1.0 and 1.0
+    pub fn new(level: f64) -> Result<Self, String> {
+        if (-1.0..=1.0).contains(&level) {
+            Ok(Self(level))
+        } else {
+            Err(format!(
+                "Invalid contrast level: {}. Expected a value between -1.0 and 1.0.",
+                level
+            ))
+        }
+    }
+
+    pub fn value(&self) -> f64 {
+        self.0
+    }
+}
+
+impl std::str::FromStr for ContrastLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "standard" => Ok(Self::STANDARD),
+            "medium" => Ok(Self::MEDIUM),
+            "high" => Ok(Self::HIGH),
+            other => other
+                .parse::<f64>()
+                .map_err(|_| {
+                    format!(
+                        "Invalid contrast level: {}. Expected standard, medium, high or a number between -1.0 and 1.0.",
+                        s
+                    )
+                })
+                .and_then(Self::new),
+        }
+    }
+}
+
+impl TryFrom<ContrastSetting> for ContrastLevel {
+    type Error = String;
+
+    fn try_from(setting: ContrastSetting) -> Result<Self, Self::Error> {
+        match setting {
+            ContrastSetting::Level(level) => Self::new(level),
+            ContrastSetting::Name(name) => name.parse(),
+        }
+    }
+}
+
+impl From<ContrastLevel> for f64 {
+    fn from(level: ContrastLevel) -> Self {
+        level.0
+    }
+}
+
+impl std::fmt::Display for ContrastLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if *self == Self::STANDARD {
+            write!(f, "standard")
+        } else if *self == Self::MEDIUM {
+            write!(f, "medium")
+        } else if *self == Self::HIGH {
+            write!(f, "high")
+        } else {
+            write!(f, "{}", self.0)
+        }
+    }
+}
+
+/// How contrast between a foreground and a background is measured
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum ContrastMetric {
+    /// WCAG 2.x luminance ratio, from 1:1 to 21:1
+    #[default]
+    Wcag,
+    /// APCA lightness contrast (Lc), which judges dark mode pairs more accurately
+    Apca,
+}
+
+impl std::fmt::Display for ContrastMetric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContrastMetric::Wcag => write!(f, "wcag"),
+            ContrastMetric::Apca => write!(f, "apca"),
+        }
+    }
+}
+
+impl ContrastMetric {
+    /// Contrast of a foreground tone on a background tone
+    ///
+    /// WCAG gives the ratio, APCA the absolute Lc value.
+    pub fn contrast_of_tones(&self, foreground_tone: f64, background_tone: f64) -> f64 {
+        match self {
+            ContrastMetric::Wcag => {
+                color::contrast_ratio_of_tones(foreground_tone, background_tone)
+            }
+            ContrastMetric::Apca => color::apca_contrast_of_luminances(
+                color::apca_luminance_of_tone(foreground_tone),
+                color::apca_luminance_of_tone(background_tone),
+            )
+            .abs(),
+        }
+    }
+
+    /// Contrast of a foreground color on a background color
+    ///
+    /// WCAG gives the ratio, APCA the absolute Lc value.
+    pub fn contrast(&self, foreground: &color::Rgb, background: &color::Rgb) -> f64 {
+        match self {
+            ContrastMetric::Wcag => {
+                let tone = |rgb: &color::Rgb| color::Lab::from_rgb(rgb).l;
+                color::contrast_ratio_of_tones(tone(foreground), tone(background))
+            }
+            ContrastMetric::Apca => color::apca_contrast(foreground, background).abs(),
+        }
+    }
+
+    /// The target in this metric equivalent to a WCAG contrast ratio
+    ///
+    /// APCA targets follow the usual correspondence: 3:1 is Lc 45, 4.5:1 is
+    /// Lc 60, 7:1 is Lc 75 and so on.
+    pub fn target(&self, ratio: f64) -> f64 {
+        const RATIO_TO_LC: [(f64, f64); 7] = [
+            (1.0, 0.0),
+            (1.5, 15.0),
+            (3.0, 45.0),
+            (4.5, 60.0),
+            (7.0, 75.0),
+            (11.0, 90.0),
+            (21.0, 105.0),
+        ];
+        match self {
+            ContrastMetric::Wcag => ratio,
+            ContrastMetric::Apca => {
+                let last = RATIO_TO_LC[RATIO_TO_LC.len() - 1];
+                if ratio >= last.0 {
+                    return last.1;
+                }
+                for pair in RATIO_TO_LC.windows(2) {
+                    let ((r0, lc0), (r1, lc1)) = (pair[0], pair[1]);
+                    if ratio <= r1 {
+                        let t = ((ratio - r0) / (r1 - r0)).max(0.0);
+                        return lc0 + (lc1 - lc0) * t;
+                    }
+                }
+                last.1
+            }
+        }
+    }
+
+    /// The lightest tone above the background that reaches the target, if any
+    fn lighter_tone(&self, background_tone: f64, target: f64) -> Option<f64> {
+        match self {
+            ContrastMetric::Wcag => color::lighter_tone(background_tone, target),
+            ContrastMetric::Apca => {
+                if self.contrast_of_tones(100.0, background_tone) < target {
+                    return None;
+                }
+                let (mut low, mut high) = (background_tone, 100.0);
+                while high - low > 0.01 {
+                    let mid = (low + high) / 2.0;
+                    if self.contrast_of_tones(mid, background_tone) >= target {
+                        high = mid;
+                    } else {
+                        low = mid;
+                    }
+                }
+                Some(high)
+            }
+        }
+    }
+
+    /// The darkest tone below the background that reaches the target, if any
+    fn darker_tone(&self, background_tone: f64, target: f64) -> Option<f64> {
+        match self {
+            ContrastMetric::Wcag => color::darker_tone(background_tone, target),
+            ContrastMetric::Apca => {
+                if self.contrast_of_tones(0.0, background_tone) < target {
+                    return None;
+                }
+                let (mut low, mut high) = (0.0, background_tone);
+                while high - low > 0.01 {
+                    let mid = (low + high) / 2.0;
+                    if self.contrast_of_tones(mid, background_tone) >= target {
+                        low = mid;
+                    } else {
+                        high = mid;
+                    }
+                }
+                Some(low)
+            }
+        }
+    }
+}
+
+/// Minimum contrast ratios a role needs against its background at reduced,
+/// standard, medium and high contrast levels
+#[derive(Debug, Clone, Copy)]
+pub struct ContrastCurve {
+    pub low: f64,
+    pub normal: f64,
+    pub medium: f64,
+    pub high: f64,
+}
+
+impl ContrastCurve {
+    pub const fn new(low: f64, normal: f64, medium: f64, high: f64) -> Self {
+        Self {
+            low,
+            normal,
+            medium,
+            high,
+        }
+    }
+
+    /// The contrast ratio required at the given contrast level
+    pub fn get(&self, level: ContrastLevel) -> f64 {
+        let level = level.value();
+        let lerp = |a: f64, b: f64, t: f64| a + (b - a) * t;
+        if level <= -1.0 {
+            self.low
+        } else if level < 0.0 {
+            lerp(self.low, self.normal, level + 1.0)
+        } else if level < 0.5 {
+            lerp(self.normal, self.medium, level / 0.5)
+        } else if level < 1.0 {
+            lerp(self.medium, self.high, (level - 0.5) / 0.5)
+        } else {
+            self.high
+        }
+    }
+}
+
+/// Text and icons drawn on a background
+pub const CURVE_ON_COLOR: ContrastCurve = ContrastCurve::new(4.5, 7.0, 11.0, 21.0);
+/// Secondary text, such as on_surface_variant
+pub const CURVE_ON_VARIANT: ContrastCurve = ContrastCurve::new(3.0, 4.5, 7.0, 11.0);
+/// Accent colors drawn on surfaces
+pub const CURVE_ACCENT: ContrastCurve = ContrastCurve::new(3.0, 4.5, 7.0, 7.0);
+/// Containers and fixed colors on surfaces
+pub const CURVE_CONTAINER: ContrastCurve = ContrastCurve::new(1.0, 1.0, 3.0, 4.5);
+/// Outlines around components
+pub const CURVE_OUTLINE: ContrastCurve = ContrastCurve::new(1.5, 3.0, 4.5, 7.0);
+
+/// Adjust a role's tone so it meets its contrast curve against a background tone
+///
+/// At standard or higher contrast the tone is kept when it already has enough
+/// contrast; at reduced contrast it is moved to the (lower) target.
+pub fn contrast_tone(
+    tone: f64,
+    background_tone: f64,
+    curve: &ContrastCurve,
+    level: ContrastLevel,
+    metric: ContrastMetric,
+) -> f64 {
+    let desired = metric.target(curve.get(level));
+    if level.value() >= 0.0 && metric.contrast_of_tones(tone, background_tone) >= desired {
+        return tone;
+    }
+    foreground_tone(background_tone, desired, metric)
+}
+
+/// Move a container tone on, away from the surface, until text can reach the
+/// target on it
+///
+/// Raised contrast can pull a container to a middle tone where neither dark
+/// (tone 10) nor light (tone 100) text reaches its target. Such a container
+/// keeps going the way it was moved, to the nearest tone where dark text (on
+/// lighter containers) or light text (on darker ones) does.
+pub fn readable_container_tone(
+    container_tone: f64,
+    surface_tone: f64,
+    target: f64,
+    metric: ContrastMetric,
+) -> f64 {
+    let reaches = |text_tone: f64, tone: f64| metric.contrast_of_tones(text_tone, tone) >= target;
+    if reaches(10.0, container_tone) || reaches(100.0, container_tone) {
+        return container_tone;
+    }
+    let (text_tone, end) = if container_tone >= surface_tone {
+        (10.0, 100.0)
+    } else {
+        (100.0, 0.0)
+    };
+    if !reaches(text_tone, end) {
+        return end;
+    }
+    let (mut near, mut far) = (container_tone, end);
+    while (far - near).abs() > 0.01 {
+        let mid = (near + far) / 2.0;
+        if reaches(text_tone, mid) {
+            far = mid;
+        } else {
+            near = mid;
+        }
+    }
+    far
+}
+
+/// The tone closest to reaching the given contrast target against a background tone
+///
+/// `target` is in the units of `metric`: a ratio for WCAG, Lc for APCA.
+pub fn foreground_tone(background_tone: f64, target: f64, metric: ContrastMetric) -> f64 {
+    let lighter = metric
+        .lighter_tone(background_tone, target)
+        .unwrap_or(100.0);
+    let darker = metric.darker_tone(background_tone, target).unwrap_or(0.0);
+    let lighter_contrast = metric.contrast_of_tones(lighter, background_tone);
+    let darker_contrast = metric.contrast_of_tones(darker, background_tone);
+
+    // Dark backgrounds prefer light foregrounds and vice versa
+    if background_tone.round() < 60.0 {
+        let negligible_difference = (lighter_contrast - darker_contrast).abs() < 0.1
+            && lighter_contrast < target
+            && darker_contrast < target;
+        if lighter_contrast >= target
+            || lighter_contrast >= darker_contrast
+            || negligible_difference
+        {
+            lighter
+        } else {
+            darker
+        }
+    } else if darker_contrast >= target || darker_contrast >= lighter_contrast {
+        darker
+    } else {
+        lighter
+    }
+}
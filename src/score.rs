@@ -0,0 +1,105 @@
+// Ranks colors by how well they would work as a theme's source color
+// Favors colors that are common in the image and have a reasonable chroma,
+// and picks colors with distinct hues when more than one is requested
+
+use crate::color::{self, Hct, Rgb};
+use std::collections::HashMap;
+
+const TARGET_CHROMA: f64 = 48.0; // A1 chroma in Material Design 3
+const WEIGHT_PROPORTION: f64 = 0.7;
+const WEIGHT_CHROMA_ABOVE: f64 = 0.3;
+const WEIGHT_CHROMA_BELOW: f64 = 0.1;
+const CUTOFF_CHROMA: f64 = 5.0;
+const CUTOFF_EXCITED_PROPORTION: f64 = 0.01;
+
+/// Google Blue, used when no color in the image is suitable
+pub const FALLBACK_COLOR: Rgb = Rgb {
+    r: 0x42,
+    g: 0x85,
+    b: 0xf4,
+};
+
+/// Pick up to `desired` source colors from a color population, best first
+///
+/// Falls back to Google Blue when every color is too gray or too rare.
+pub fn score(colors_to_population: &HashMap<Rgb, u32>, desired: usize) -> Vec<Rgb> {
+    // Sort so results don't depend on hash map iteration order
+    let mut population: Vec<(Rgb, u32)> = colors_to_population
+        .iter()
+        .map(|(rgb, count)| (*rgb, *count))
+        .collect();
+    population.sort_by_key(|(c, _)| (c.r, c.g, c.b));
+
+    let population_sum: f64 = population.iter().map(|(_, count)| *count as f64).sum();
+    if population_sum == 0.0 {
+        return vec![FALLBACK_COLOR];
+    }
+
+    let mut hue_population = [0.0; 360];
+    let colors_hct: Vec<(Rgb, Hct)> = population
+        .iter()
+        .map(|(rgb, count)| {
+            let hct = color::rgb_to_hct(rgb.r, rgb.g, rgb.b);
+            hue_population[hue_index(hct.h)] += *count as f64;
+            (*rgb, hct)
+        })
+        .collect();
+
+    // Share of the image with a hue within 15 degrees of each hue
+    let mut hue_excited_proportions = [0.0; 360];
+    for (hue, population) in hue_population.iter().enumerate() {
+        let proportion = population / population_sum;
+        for offset in -14..=15 {
+            let neighbor = (hue as i32 + offset).rem_euclid(360) as usize;
+            hue_excited_proportions[neighbor] += proportion;
+        }
+    }
+
+    let mut scored: Vec<(Rgb, Hct, f64)> = colors_hct
+        .into_iter()
+        .filter_map(|(rgb, hct)| {
+            let proportion = hue_excited_proportions[hue_index(hct.h)];
+            if hct.c < CUTOFF_CHROMA || proportion <= CUTOFF_EXCITED_PROPORTION {
+                return None;
+            }
+            let proportion_score = proportion * 100.0 * WEIGHT_PROPORTION;
+            let chroma_weight = if hct.c < TARGET_CHROMA {
+                WEIGHT_CHROMA_BELOW
+            } else {
+                WEIGHT_CHROMA_ABOVE
+            };
+            let chroma_score = (hct.c - TARGET_CHROMA) * chroma_weight;
+            Some((rgb, hct, proportion_score + chroma_score))
+        })
+        .collect();
+    scored.sort_by(|a, b| b.2.total_cmp(&a.2));
+
+    // Prefer hues far apart, relaxing the distance until enough colors are found
+    let mut chosen: Vec<(Rgb, f64)> = Vec::new();
+    for difference_degrees in (15..=90).rev() {
+        chosen.clear();
+        for (rgb, hct, _) in &scored {
+            let distinct = chosen.iter().all(|(_, hue)| {
+                color::difference_degrees(hct.h, *hue) >= difference_degrees as f64
+            });
+            if distinct {
+                chosen.push((*rgb, hct.h));
+            }
+            if chosen.len() >= desired {
+                break;
+            }
+        }
+        if chosen.len() >= desired {
+            break;
+        }
+    }
+
+    if chosen.is_empty() {
+        return vec![FALLBACK_COLOR];
+    }
+    chosen.into_iter().map(|(rgb, _)| rgb).collect()
+}
+
+fn hue_index(hue: f64) -> usize {
+    (color::sanitize_degrees(hue.round()) as usize) % 360
+}
@@ -1,9 +1,15 @@
+use crate::ansi::AnsiPalette;
+use crate::base16;
 use crate::color;
+use crate::pywal;
+use crate::scheme::{self, ContrastCurve, ContrastLevel, ContrastMetric, Scheme};
+use crate::theme_file::{Theme, ThemeColor, ThemeMode};
 use regex::Regex;
 use serde_json::Value;
+use std::cell::OnceCell;
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone)]
 pub struct ColorFormat {
@@ -13,20 +19,64 @@ pub struct ColorFormat {
     pub rgba: String,
     pub hsl: String,
     pub hsla: String,
+    pub oklab: String,
+    pub oklch: String,
+    pub p3: String,
     pub red: u8,
     pub green: u8,
     pub blue: u8,
     pub alpha: u8,
+    /// Nearest xterm 256-color index (16-255)
+    pub ansi256: u8,
+    /// Nearest basic 16-color index, assuming xterm's default colors
+    pub ansi16: u8,
     pub hue: f64,
     pub saturation: f64,
     pub lightness: f64,
 }
 
+impl ColorFormat {
+    /// The color without its alpha channel
+    pub fn rgb(&self) -> color::Rgb {
+        color::Rgb {
+            r: self.red,
+            g: self.green,
+            b: self.blue,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ColorEntry {
     pub default: ColorFormat,
 }
 
+/// Tonal palettes the palette roles are derived from
+#[derive(Debug, Clone)]
+pub struct TonalPalettes {
+    pub primary: color::TonalPalette,
+    pub secondary: color::TonalPalette,
+    pub tertiary: color::TonalPalette,
+    pub neutral: color::TonalPalette,
+    pub neutral_variant: color::TonalPalette,
+    pub error: color::TonalPalette,
+}
+
+impl TonalPalettes {
+    /// Look up a tonal palette by its template name
+    pub fn get(&self, name: &str) -> Option<&color::TonalPalette> {
+        match name {
+            "primary" => Some(&self.primary),
+            "secondary" => Some(&self.secondary),
+            "tertiary" => Some(&self.tertiary),
+            "neutral" => Some(&self.neutral),
+            "neutral_variant" => Some(&self.neutral_variant),
+            "error" => Some(&self.error),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Palette {
     pub primary: ColorEntry,
@@ -94,18 +144,134 @@ pub struct Palette {
     // Other colors
     pub shadow: ColorEntry,
     pub scrim: ColorEntry,
+
+    // Custom named colors from the theme
+    pub custom: Vec<CustomColor>,
+
+    // ANSI terminal colors
+    pub ansi: AnsiPalette,
+
+    // Tonal palettes (tones 0-100)
+    pub palettes: TonalPalettes,
+}
+
+impl Palette {
+    /// Built-in roles by their template name
+    pub fn roles(&self) -> Vec<(&'static str, &ColorFormat)> {
+        vec![
+            ("primary", &self.primary.default),
+            ("on_primary", &self.on_primary.default),
+            ("primary_container", &self.primary_container.default),
+            ("on_primary_container", &self.on_primary_container.default),
+            ("secondary", &self.secondary.default),
+            ("on_secondary", &self.on_secondary.default),
+            ("secondary_container", &self.secondary_container.default),
+            (
+                "on_secondary_container",
+                &self.on_secondary_container.default,
+            ),
+            ("tertiary", &self.tertiary.default),
+            ("on_tertiary", &self.on_tertiary.default),
+            ("tertiary_container", &self.tertiary_container.default),
+            ("on_tertiary_container", &self.on_tertiary_container.default),
+            ("error", &self.error.default),
+            ("on_error", &self.on_error.default),
+            ("error_container", &self.error_container.default),
+            ("on_error_container", &self.on_error_container.default),
+            ("background", &self.background.default),
+            ("on_background", &self.on_background.default),
+            ("surface", &self.surface.default),
+            ("on_surface", &self.on_surface.default),
+            ("surface_variant", &self.surface_variant.default),
+            ("on_surface_variant", &self.on_surface_variant.default),
+            (
+                "surface_container_lowest",
+                &self.surface_container_lowest.default,
+            ),
+            ("surface_container_low", &self.surface_container_low.default),
+            ("surface_container", &self.surface_container.default),
+            (
+                "surface_container_high",
+                &self.surface_container_high.default,
+            ),
+            (
+                "surface_container_highest",
+                &self.surface_container_highest.default,
+            ),
+            ("outline", &self.outline.default),
+            ("outline_variant", &self.outline_variant.default),
+            ("shadow", &self.shadow.default),
+        ]
+    }
+
+    /// Tonal palette by its template name, custom colors included
+    pub fn tonal_palette(&self, name: &str) -> Option<&color::TonalPalette> {
+        self.palettes.get(name).or_else(|| {
+            self.custom
+                .iter()
+                .find(|custom| custom.name == name)
+                .map(|custom| &custom.palette)
+        })
+    }
 }
 
-/// Create a color format from a hex string
-fn create_color_format(hex: &str) -> Result<ColorFormat, String> {
-    let rgb = color::hex_to_rgb(hex)?;
+/// A named color from the theme's `custom` object, with its four roles
+#[derive(Debug)]
+pub struct CustomColor {
+    pub name: String,
+    pub color: ColorEntry,
+    pub on_color: ColorEntry,
+    pub color_container: ColorEntry,
+    pub on_color_container: ColorEntry,
+    /// Tonal palette of the color, after harmonization
+    pub palette: color::TonalPalette,
+}
+
+impl CustomColor {
+    /// Template names of the roles, e.g. `success`, `on_success`,
+    /// `success_container` and `on_success_container`
+    pub fn roles(&self) -> [(String, &ColorFormat); 4] {
+        let [color, on_color, container, on_container] = custom_role_names(&self.name);
+        [
+            (color, &self.color.default),
+            (on_color, &self.on_color.default),
+            (container, &self.color_container.default),
+            (on_container, &self.on_color_container.default),
+        ]
+    }
+}
+
+fn custom_role_names(name: &str) -> [String; 4] {
+    [
+        name.to_string(),
+        format!("on_{}", name),
+        format!("{}_container", name),
+        format!("on_{}_container", name),
+    ]
+}
+
+/// Create a color format from a color in any supported syntax
+fn create_color_format(color: &str) -> Result<ColorFormat, String> {
+    Ok(color_format_from_rgba(color::parse_color(color)?))
+}
+
+/// Create a color format from an RGB color and its alpha
+fn color_format_from_rgba(rgba: color::Rgba) -> ColorFormat {
+    let rgb = rgba.rgb();
     let hsl = color::rgb_to_hsl(rgb.r as f64, rgb.g as f64, rgb.b as f64);
+    let hex = color::rgb_to_hex(rgb.r as f64, rgb.g as f64, rgb.b as f64);
 
-    Ok(ColorFormat {
-        hex: hex.to_string(),
+    ColorFormat {
         hex_stripped: hex.trim_start_matches('#').to_string(),
+        hex,
         rgb: format!("rgb({}, {}, {})", rgb.r, rgb.g, rgb.b),
-        rgba: format!("rgba({}, {}, {}, {})", rgb.r, rgb.g, rgb.b, 255),
+        rgba: format!(
+            "rgba({}, {}, {}, {})",
+            rgb.r,
+            rgb.g,
+            rgb.b,
+            format_alpha(rgba.alpha())
+        ),
         hsl: format!(
             "hsl({}, {}%, {}%)",
             (hsl.h as u32) % 360,
@@ -113,32 +279,56 @@ fn create_color_format(hex: &str) -> Result<ColorFormat, String> {
             (hsl.l as u32).min(100)
         ),
         hsla: format!(
-            "hsla({}, {}%, {}%, 1.0)",
+            "hsla({}, {}%, {}%, {})",
             (hsl.h as u32) % 360,
             (hsl.s as u32).min(100),
-            (hsl.l as u32).min(100)
+            (hsl.l as u32).min(100),
+            format_alpha(rgba.alpha())
         ),
+        oklab: color::Oklab::from_rgb(&rgb).to_css(),
+        oklch: color::Oklch::from_rgb(&rgb).to_css(),
+        p3: color::DisplayP3::from_rgb(&rgb).to_css(),
         red: rgb.r,
         green: rgb.g,
         blue: rgb.b,
-        alpha: 255,
+        alpha: rgba.a,
+        ansi256: color::nearest_ansi256(&rgb),
+        ansi16: color::nearest_ansi16(&rgb),
         hue: hsl.h,
         saturation: hsl.s,
         lightness: hsl.l,
-    })
+    }
+}
+
+/// Create a color format for a tone of a tonal palette
+///
+/// The Display P3 value keeps chroma that has to be given up in sRGB.
+pub(crate) fn tone_color_format(palette: &color::TonalPalette, tone: f64) -> ColorFormat {
+    let mut color_format = color_format_from_rgba(palette.tone(tone).into());
+    color_format.p3 = palette.hct(tone).to_display_p3().to_css();
+    color_format
 }
 
-/// Load theme JSON file
+/// Format an alpha fraction with up to two decimals, e.g. `1.0` or `0.8`
+fn format_alpha(alpha: f64) -> String {
+    let alpha = (alpha * 100.0).round() / 100.0;
+    if alpha.fract() == 0.0 {
+        format!("{:.1}", alpha)
+    } else {
+        format!("{}", alpha)
+    }
+}
+
+/// Load a theme file: JSON, TOML or YAML, depending on its extension
+///
+/// A theme can build on another one with `"extends": "name"`, at the top level
+/// or inside a mode. Its keys are deep-merged over the parent's.
 pub fn load_theme(theme_path: &str) -> Result<Value, String> {
     if crate::log::is_verbose() {
         eprintln!("Loading theme from {}", theme_path);
     }
 
-    let content = fs::read_to_string(theme_path)
-        .map_err(|e| format!("Could not read theme file '{}': {}", theme_path, e))?;
-
-    let theme_data: Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Invalid JSON format in '{}': {}", theme_path, e))?;
+    let theme_data = load_theme_file(Path::new(theme_path), &mut Vec::new())?;
 
     if crate::log::is_verbose() {
         eprintln!("Theme loaded successfully from {}", theme_path);
@@ -146,23 +336,198 @@ pub fn load_theme(theme_path: &str) -> Result<Value, String> {
     Ok(theme_data)
 }
 
-/// Select theme mode, defaulting to dark if requested mode not found
-pub fn select_theme_mode(theme_all: &Value, mode: &str) -> Result<(Value, String), String> {
-    if let Some(theme_mode) = theme_all.get(mode) {
-        Ok((theme_mode.clone(), mode.to_string()))
-    } else {
-        eprintln!("Mode '{}' not found in theme.json. Using 'dark'.", mode);
-        if let Some(dark_mode) = theme_all.get("dark") {
-            Ok((dark_mode.clone(), "dark".to_string()))
-        } else {
-            Err(
-                "Error: 'dark' mode not available in theme.json and requested mode not found."
-                    .to_string(),
-            )
+/// Read and check a theme file, then resolve what it extends
+///
+/// `chain` holds the files being loaded, from the one the user asked for to
+/// this one's child, so that a file extending one of them is a cycle.
+fn load_theme_file(path: &Path, chain: &mut Vec<PathBuf>) -> Result<Value, String> {
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if let Some(start) = chain.iter().position(|loaded| *loaded == canonical) {
+        let cycle: Vec<String> = chain[start..]
+            .iter()
+            .chain(std::iter::once(&canonical))
+            .map(|path| format!("'{}'", path.display()))
+            .collect();
+        return Err(format!("Theme inheritance cycle: {}", cycle.join(" -> ")));
+    }
+
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Could not read theme file '{}': {}", path.display(), e))?;
+    let mut theme_data = parse_theme_content(&content, path)?;
+    if base16::is_base16_scheme(&theme_data) {
+        theme_data = base16::theme_from_base16(&theme_data)
+            .map_err(|e| format!("Invalid base16 scheme '{}': {}", path.display(), e))?;
+    } else if pywal::is_pywal_colors(&theme_data) {
+        theme_data = pywal::theme_from_pywal(&theme_data)
+            .map_err(|e| format!("Invalid pywal colors '{}': {}", path.display(), e))?;
+    }
+    // Each file is checked on its own so that errors name the file they are in;
+    // the merged theme is deserialized again when a palette is generated
+    Theme::from_value(&theme_data)
+        .map_err(|e| format!("Invalid theme '{}': {}", path.display(), e))?;
+
+    chain.push(canonical);
+    let resolved = resolve_extends(&mut theme_data, path, chain);
+    chain.pop();
+    resolved.map(|()| theme_data)
+}
+
+/// Parse a theme file with the parser its extension calls for; JSON is the default
+fn parse_theme_content(content: &str, path: &Path) -> Result<Value, String> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("toml") => toml::from_str(content)
+            .map_err(|e| format!("Invalid TOML format in '{}': {}", path.display(), e)),
+        Some("yaml" | "yml") => serde_norway::from_str(content)
+            .map_err(|e| format!("Invalid YAML format in '{}': {}", path.display(), e)),
+        _ => serde_json::from_str(content)
+            .map_err(|e| format!("Invalid JSON format in '{}': {}", path.display(), e)),
+    }
+}
+
+/// Merge the parents named by `extends` keys under a theme's own keys
+///
+/// Modes are resolved first, so a mode's parent sits between the theme's
+/// top-level parent and the mode's own keys.
+fn resolve_extends(theme: &mut Value, path: &Path, chain: &mut Vec<PathBuf>) -> Result<(), String> {
+    let mut load_parent = |name: &str| {
+        let parent_path = resolve_parent_theme(name, path)?;
+        load_theme_file(&parent_path, chain)
+    };
+    // Errors from a parent name every theme that extends it
+    let extended_by = |e: String| format!("{}\n  extended by '{}'", e, path.display());
+
+    for mode in ["dark", "light"] {
+        let Some(name) = take_extends(theme.get_mut(mode)) else {
+            continue;
+        };
+        let parent = load_parent(&name).map_err(extended_by)?;
+        let mut parent_mode = parent.get(mode).cloned().ok_or_else(|| {
+            extended_by(format!("Theme '{}' has no '{}' mode to extend", name, mode))
+        })?;
+        merge_theme_values(&mut parent_mode, theme[mode].take());
+        theme[mode] = parent_mode;
+    }
+
+    if let Some(name) = take_extends(Some(theme)) {
+        let mut parent = load_parent(&name).map_err(extended_by)?;
+        merge_theme_values(&mut parent, theme.take());
+        *theme = parent;
+    }
+    Ok(())
+}
+
+/// Remove and return the `extends` key of a theme or mode object
+fn take_extends(value: Option<&mut Value>) -> Option<String> {
+    match value?.as_object_mut()?.remove("extends")? {
+        Value::String(name) => Some(name),
+        _ => None,
+    }
+}
+
+/// Deep-merge `overlay` into `base`: objects are merged key by key, other values replaced
+fn merge_theme_values(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_theme_values(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
         }
+        (base, overlay) => *base = overlay,
     }
 }
 
+/// Find the theme an `extends` key names: next to the extending theme first,
+/// then wherever `--theme` would find it
+fn resolve_parent_theme(name: &str, child_path: &Path) -> Result<PathBuf, String> {
+    let dir = child_path.parent().unwrap_or(Path::new("."));
+    Some(dir.join(name))
+        .filter(|path| path.is_file())
+        .or_else(|| find_theme_in(dir, name))
+        .map_or_else(|| resolve_theme_path(name).map(PathBuf::from), Ok)
+}
+
+/// Extensions of theme files, in the order they are looked for
+const THEME_EXTENSIONS: [&str; 4] = ["json", "toml", "yaml", "yml"];
+
+/// The theme file called `name` in a directory, with any theme extension
+fn find_theme_in(dir: &Path, name: &str) -> Option<PathBuf> {
+    THEME_EXTENSIONS
+        .iter()
+        .map(|extension| dir.join(format!("{}.{}", name, extension)))
+        .find(|path| path.is_file())
+}
+
+/// Find a theme by path or by name
+///
+/// Looks for the path itself, then for `<name>.json`, `.toml`, `.yaml` or
+/// `.yml` in the project's `themes/` directory and in `~/.config/tinct/themes/`.
+pub fn resolve_theme_path(theme_name: &str) -> Result<String, String> {
+    // First, check if the theme path is provided as an absolute path
+    if Path::new(theme_name).is_absolute() && Path::new(theme_name).exists() {
+        return Ok(theme_name.to_string());
+    }
+
+    // Check if it's a relative path that exists from current directory
+    if Path::new(theme_name).exists() {
+        return Ok(Path::new(theme_name)
+            .canonicalize()
+            .unwrap_or_else(|_| PathBuf::from(theme_name))
+            .to_string_lossy()
+            .to_string());
+    }
+
+    // Check in project's themes directory
+    let project_themes_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("themes");
+    if let Some(project_themes_path) = find_theme_in(&project_themes_dir, theme_name) {
+        return Ok(project_themes_path.to_string_lossy().to_string());
+    }
+
+    // Check in user's config directory ~/.config/tinct/themes/
+    if let Ok(home_dir) = std::env::var("HOME") {
+        let user_themes_dir = Path::new(&home_dir)
+            .join(".config")
+            .join("tinct")
+            .join("themes");
+        if let Some(user_themes_path) = find_theme_in(&user_themes_dir, theme_name) {
+            return Ok(user_themes_path.to_string_lossy().to_string());
+        }
+    }
+
+    Err(format!(
+        "Theme '{}' not found in any of these locations:\n  - Current directory\n  - Project themes/ directory\n  - ~/.config/tinct/themes/",
+        theme_name
+    ))
+}
+
+/// Build theme data for both modes from a single source color
+///
+/// Only the primary color is set, so it is meant to be used with a dynamic scheme.
+pub fn seed_theme(source_hex: &str) -> Value {
+    serde_json::json!({
+        "dark": { "primary": source_hex },
+        "light": { "primary": source_hex },
+    })
+}
+
+/// Select theme mode, defaulting to dark if requested mode not found
+///
+/// The mode is returned as written. Palettes are generated from
+/// [`Theme::mode`], which also fills in the theme's shared custom colors and
+/// adjustments.
+pub fn select_theme_mode(theme_all: &Value, mode: &str) -> Result<(Value, String), String> {
+    let (_, effective_mode) = Theme::from_value(theme_all)?.mode(mode)?;
+    Ok((theme_all[&effective_mode].clone(), effective_mode))
+}
+
 /// Load template file
 pub fn load_template(template_path: &str) -> Result<String, String> {
     if crate::log::is_verbose() {
@@ -178,6 +543,73 @@ pub fn load_template(template_path: &str) -> Result<String, String> {
     Ok(template_content)
 }
 
+/// Color properties available on every color placeholder
+const COLOR_PROPERTIES: [&str; 18] = [
+    "hex",
+    "hex_stripped",
+    "rgb",
+    "rgba",
+    "hsl",
+    "hsla",
+    "oklab",
+    "oklch",
+    "p3",
+    "red",
+    "green",
+    "blue",
+    "alpha",
+    "ansi256",
+    "ansi16",
+    "hue",
+    "saturation",
+    "lightness",
+];
+
+/// Render a single color property for a template placeholder
+fn color_property(color_format: &ColorFormat, prop: &str) -> String {
+    match prop {
+        "hex" => color_format.hex.clone(),
+        "hex_stripped" => color_format.hex_stripped.clone(),
+        "rgb" => color_format.rgb.clone(),
+        "rgba" => color_format.rgba.clone(),
+        "hsl" => color_format.hsl.clone(),
+        "hsla" => color_format.hsla.clone(),
+        "oklab" => color_format.oklab.clone(),
+        "oklch" => color_format.oklch.clone(),
+        "p3" => color_format.p3.clone(),
+        "red" => color_format.red.to_string(),
+        "green" => color_format.green.to_string(),
+        "blue" => color_format.blue.to_string(),
+        "alpha" => color_format.alpha.to_string(),
+        "ansi256" => color_format.ansi256.to_string(),
+        "ansi16" => color_format.ansi16.to_string(),
+        "hue" => format!("{:.0}", color_format.hue),
+        "saturation" => format!("{:.0}", color_format.saturation),
+        "lightness" => format!("{:.0}", color_format.lightness),
+        _ => "#000000".to_string(), // default fallback
+    }
+}
+
+/// Value used for a color property when the color is not found
+fn default_color_property(prop: &str) -> String {
+    match prop {
+        "hex" => "#000000".to_string(),
+        "hex_stripped" => "000000".to_string(),
+        "red" | "green" | "blue" | "alpha" => "0".to_string(),
+        "ansi256" => "16".to_string(),
+        "ansi16" => "0".to_string(),
+        "hue" | "saturation" | "lightness" => "0".to_string(),
+        "rgb" => "rgb(0, 0, 0)".to_string(),
+        "rgba" => "rgba(0, 0, 0, 0)".to_string(),
+        "hsl" => "hsl(0, 0%, 0%)".to_string(),
+        "hsla" => "hsla(0, 0%, 0%, 1.0)".to_string(),
+        "oklab" => "oklab(0.00% 0.0000 0.0000)".to_string(),
+        "oklch" => "oklch(0.00% 0.0000 0.00)".to_string(),
+        "p3" => "color(display-p3 0.0000 0.0000 0.0000)".to_string(),
+        _ => "#000000".to_string(),
+    }
+}
+
 /// Process template by replacing color placeholders and mode placeholders
 pub fn process_template(template_content: &str, palette: &Palette, effective_mode: &str) -> String {
     if crate::log::is_verbose() {
@@ -202,82 +634,18 @@ pub fn process_template(template_content: &str, palette: &Palette, effective_mod
         },
     );
 
-    // Create a mapping of color names to color entries
-    let color_map: HashMap<&str, &ColorFormat> = [
-        ("primary", &palette.primary.default),
-        ("on_primary", &palette.on_primary.default),
-        ("primary_container", &palette.primary_container.default),
-        (
-            "on_primary_container",
-            &palette.on_primary_container.default,
-        ),
-        ("secondary", &palette.secondary.default),
-        ("on_secondary", &palette.on_secondary.default),
-        ("secondary_container", &palette.secondary_container.default),
-        (
-            "on_secondary_container",
-            &palette.on_secondary_container.default,
-        ),
-        ("tertiary", &palette.tertiary.default),
-        ("on_tertiary", &palette.on_tertiary.default),
-        ("tertiary_container", &palette.tertiary_container.default),
-        (
-            "on_tertiary_container",
-            &palette.on_tertiary_container.default,
-        ),
-        ("error", &palette.error.default),
-        ("on_error", &palette.on_error.default),
-        ("error_container", &palette.error_container.default),
-        ("on_error_container", &palette.on_error_container.default),
-        ("background", &palette.background.default),
-        ("on_background", &palette.on_background.default),
-        ("surface", &palette.surface.default),
-        ("on_surface", &palette.on_surface.default),
-        ("surface_variant", &palette.surface_variant.default),
-        ("on_surface_variant", &palette.on_surface_variant.default),
-        (
-            "surface_container_lowest",
-            &palette.surface_container_lowest.default,
-        ),
-        (
-            "surface_container_low",
-            &palette.surface_container_low.default,
-        ),
-        ("surface_container", &palette.surface_container.default),
-        (
-            "surface_container_high",
-            &palette.surface_container_high.default,
-        ),
-        (
-            "surface_container_highest",
-            &palette.surface_container_highest.default,
-        ),
-        ("outline", &palette.outline.default),
-        ("outline_variant", &palette.outline_variant.default),
-        ("shadow", &palette.shadow.default),
-    ]
-    .iter()
-    .cloned()
-    .collect();
+    // Create a mapping of color names to color entries, custom colors included
+    let mut color_map: HashMap<String, &ColorFormat> = palette
+        .roles()
+        .into_iter()
+        .map(|(name, color_format)| (name.to_string(), color_format))
+        .collect();
+    for custom in &palette.custom {
+        color_map.extend(custom.roles());
+    }
 
     // Replace all color property placeholders
-    let color_properties = [
-        "hex",
-        "hex_stripped",
-        "rgb",
-        "rgba",
-        "hsl",
-        "hsla",
-        "red",
-        "green",
-        "blue",
-        "alpha",
-        "hue",
-        "saturation",
-        "lightness",
-    ];
-
-    for prop in &color_properties {
+    for prop in &COLOR_PROPERTIES {
         let pattern = format!(
             r"\{{\{{\s*colors\.([a-zA-Z0-9_]+)\.default\.{}\s*\}}\}}",
             regex::escape(prop)
@@ -288,35 +656,54 @@ pub fn process_template(template_content: &str, palette: &Palette, effective_mod
             .replace_all(&content, |caps: &regex::Captures| {
                 let key = &caps[1];
                 if let Some(color_format) = color_map.get(key) {
-                    match *prop {
-                        "hex" => color_format.hex.clone(),
-                        "hex_stripped" => color_format.hex_stripped.clone(),
-                        "rgb" => color_format.rgb.clone(),
-                        "rgba" => color_format.rgba.clone(),
-                        "hsl" => color_format.hsl.clone(),
-                        "hsla" => color_format.hsla.clone(),
-                        "red" => color_format.red.to_string(),
-                        "green" => color_format.green.to_string(),
-                        "blue" => color_format.blue.to_string(),
-                        "alpha" => color_format.alpha.to_string(),
-                        "hue" => format!("{:.0}", color_format.hue),
-                        "saturation" => format!("{:.0}", color_format.saturation),
-                        "lightness" => format!("{:.0}", color_format.lightness),
-                        _ => "#000000".to_string(), // default fallback
-                    }
+                    color_property(color_format, prop)
                 } else {
                     // Return default value if color key is not found
-                    match *prop {
-                        "hex" => "#000000".to_string(),
-                        "hex_stripped" => "000000".to_string(),
-                        "red" | "green" | "blue" | "alpha" => "0".to_string(),
-                        "hue" | "saturation" | "lightness" => "0".to_string(),
-                        "rgb" => "rgb(0, 0, 0)".to_string(),
-                        "rgba" => "rgba(0, 0, 0, 0)".to_string(),
-                        "hsl" => "hsl(0, 0%, 0%)".to_string(),
-                        "hsla" => "hsla(0, 0%, 0%, 1.0)".to_string(),
-                        _ => "#000000".to_string(),
+                    default_color_property(prop)
+                }
+            })
+            .to_string();
+    }
+
+    // Replace ANSI color placeholders, e.g. {{colors.ansi.bright_red.hex}} or {{colors.ansi.color9.hex}}
+    for prop in &COLOR_PROPERTIES {
+        let pattern = format!(
+            r"\{{\{{\s*colors\.ansi\.([a-zA-Z0-9_]+)\.{}\s*\}}\}}",
+            regex::escape(prop)
+        );
+        let re = Regex::new(&pattern).unwrap();
+
+        content = re
+            .replace_all(&content, |caps: &regex::Captures| {
+                match palette.ansi.get(&caps[1]) {
+                    Some(color_format) => color_property(color_format, prop),
+                    None => default_color_property(prop),
+                }
+            })
+            .to_string();
+    }
+
+    // Replace tonal palette placeholders, e.g. {{colors.palettes.primary.tone_40.hex}}
+    // Each tone is formatted once, however many of its properties are used
+    let mut tone_formats: HashMap<(String, u32), ColorFormat> = HashMap::new();
+    for prop in &COLOR_PROPERTIES {
+        let pattern = format!(
+            r"\{{\{{\s*colors\.palettes\.([a-zA-Z0-9_]+)\.tone_([0-9]+)\.{}\s*\}}\}}",
+            regex::escape(prop)
+        );
+        let re = Regex::new(&pattern).unwrap();
+
+        content = re
+            .replace_all(&content, |caps: &regex::Captures| {
+                let tone = caps[2].parse::<u32>().ok().filter(|t| *t <= 100);
+                match (palette.tonal_palette(&caps[1]), tone) {
+                    (Some(tonal_palette), Some(tone)) => {
+                        let color_format = tone_formats
+                            .entry((caps[1].to_string(), tone))
+                            .or_insert_with(|| tone_color_format(tonal_palette, tone as f64));
+                        color_property(color_format, prop)
                     }
+                    _ => default_color_property(prop),
                 }
             })
             .to_string();
@@ -356,187 +743,345 @@ pub fn save_output(content: &str, output_path: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Settings that control how a palette is generated from a theme
+#[derive(Debug, Clone)]
+pub struct PaletteOptions {
+    /// Dynamic scheme variant; `None` uses the theme's colors as written
+    pub scheme: Option<Scheme>,
+    /// How far apart role tones are moved to meet contrast targets
+    pub contrast: ContrastLevel,
+    /// How contrast between roles is measured
+    pub metric: ContrastMetric,
+    /// Lift dark yellow-green source colors to a lighter tone
+    pub fix_disliked: bool,
+    /// Shifts applied to the theme's colors; unset ones fall back to the theme's keys
+    pub adjustments: PaletteAdjustments,
+}
+
+impl Default for PaletteOptions {
+    fn default() -> Self {
+        Self {
+            scheme: None,
+            contrast: ContrastLevel::default(),
+            metric: ContrastMetric::default(),
+            fix_disliked: true,
+            adjustments: PaletteAdjustments::default(),
+        }
+    }
+}
+
+/// Global shifts applied in HCT to the theme's colors before roles are derived
+///
+/// Unset fields leave the colors unchanged.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PaletteAdjustments {
+    /// Degrees added to every hue
+    pub hue_shift: Option<f64>,
+    /// Factor every chroma is multiplied by
+    pub chroma_scale: Option<f64>,
+    /// Tones added to every tone, clamped to 0-100
+    pub tone_offset: Option<f64>,
+}
+
+impl PaletteAdjustments {
+    /// Fill the fields this one leaves unset from `other`
+    pub fn or(self, other: PaletteAdjustments) -> PaletteAdjustments {
+        PaletteAdjustments {
+            hue_shift: self.hue_shift.or(other.hue_shift),
+            chroma_scale: self.chroma_scale.or(other.chroma_scale),
+            tone_offset: self.tone_offset.or(other.tone_offset),
+        }
+    }
+
+    /// Read the adjustment keys of a theme mode, e.g. `"hue_shift": 10`
+    pub fn from_theme(theme: &ThemeMode) -> Result<PaletteAdjustments, String> {
+        let adjustments = PaletteAdjustments {
+            hue_shift: theme.hue_shift,
+            chroma_scale: theme.chroma_scale,
+            tone_offset: theme.tone_offset,
+        };
+        adjustments.validate()?;
+        Ok(adjustments)
+    }
+
+    /// Check that the adjustments can be applied: the chroma scale must not be negative
+    pub fn validate(&self) -> Result<(), String> {
+        match self.chroma_scale {
+            Some(scale) if scale < 0.0 => {
+                Err(format!("Chroma scale must not be negative, got {}", scale))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether applying these adjustments leaves every color unchanged
+    pub fn is_identity(&self) -> bool {
+        self.hue_shift.unwrap_or(0.0) == 0.0
+            && self.chroma_scale.unwrap_or(1.0) == 1.0
+            && self.tone_offset.unwrap_or(0.0) == 0.0
+    }
+
+    /// Apply the adjustments to a color
+    pub fn apply(&self, hct: &color::Hct) -> color::Hct {
+        color::Hct::from_hct(
+            color::sanitize_degrees(hct.h + self.hue_shift.unwrap_or(0.0)),
+            hct.c * self.chroma_scale.unwrap_or(1.0).max(0.0),
+            (hct.t + self.tone_offset.unwrap_or(0.0)).clamp(0.0, 100.0),
+        )
+    }
+
+    /// Tonal palettes with their chroma scaled
+    fn apply_to_palettes(&self, palettes: TonalPalettes) -> TonalPalettes {
+        let scale = |palette: color::TonalPalette| {
+            color::TonalPalette::from_hue_and_chroma(
+                palette.hue,
+                palette.chroma * self.chroma_scale.unwrap_or(1.0).max(0.0),
+            )
+        };
+        TonalPalettes {
+            primary: scale(palettes.primary),
+            secondary: scale(palettes.secondary),
+            tertiary: scale(palettes.tertiary),
+            neutral: scale(palettes.neutral),
+            neutral_variant: scale(palettes.neutral_variant),
+            error: scale(palettes.error),
+        }
+    }
+
+    /// A copy of a theme mode with every color adjusted, keeping alpha
+    fn apply_to_theme(&self, theme: &ThemeMode) -> ThemeMode {
+        let mut theme = theme.clone();
+        for ThemeColor(rgba) in theme.colors_mut() {
+            let rgb = rgba.rgb();
+            *rgba = self
+                .apply(&color::rgb_to_hct(rgb.r, rgb.g, rgb.b))
+                .to_rgb()
+                .with_alpha(rgba.a);
+        }
+        theme
+    }
+}
+
 /// Generate color palette from theme data using HCT (Hue-Chroma-Tone) color space
 pub fn generate_palette(
     theme: &Value,
     is_dark_mode: bool,
+    is_strict: bool,
+) -> Result<Palette, String> {
+    generate_palette_with_options(
+        &ThemeMode::from_value(theme)?,
+        is_dark_mode,
+        is_strict,
+        &PaletteOptions::default(),
+    )
+}
+
+/// Generate a color palette from a single source color, without a theme file
+///
+/// Uses the tonal spot scheme unless `options` chooses another one.
+pub fn palette_from_seed(
+    source_hex: &str,
+    is_dark_mode: bool,
+    options: &PaletteOptions,
+) -> Result<Palette, String> {
+    let options = PaletteOptions {
+        scheme: Some(options.scheme.unwrap_or(Scheme::TonalSpot)),
+        ..options.clone()
+    };
+    let theme = ThemeMode {
+        primary: Some(ThemeColor(color::parse_color(source_hex)?)),
+        ..Default::default()
+    };
+    generate_palette_with_options(&theme, is_dark_mode, false, &options)
+}
+
+/// Generate color palette from theme data with the given generation settings
+pub fn generate_palette_with_options(
+    theme: &ThemeMode,
+    is_dark_mode: bool,
     _is_strict: bool,
+    options: &PaletteOptions,
 ) -> Result<Palette, String> {
     if crate::log::is_verbose() {
         eprintln!("Generating color palette...");
     }
 
-    // Get colors from theme - try both standard and m-prefixed keys
-    let primary_hex = theme
-        .get("primary")
-        .and_then(|v| v.as_str())
-        .or_else(|| theme.get("mPrimary").and_then(|v| v.as_str()))
-        .ok_or("Primary color not found in theme")?;
-
-    let secondary_hex = theme
-        .get("secondary")
-        .and_then(|v| v.as_str())
-        .or_else(|| theme.get("mSecondary").and_then(|v| v.as_str()))
-        .unwrap_or(primary_hex); // Fallback to primary if not specified
-
-    let tertiary_hex = theme
-        .get("tertiary")
-        .and_then(|v| v.as_str())
-        .or_else(|| theme.get("mTertiary").and_then(|v| v.as_str()))
-        .unwrap_or(secondary_hex); // Fallback to secondary if not specified
-
-    let error_hex = theme
-        .get("error")
-        .and_then(|v| v.as_str())
-        .or_else(|| theme.get("mError").and_then(|v| v.as_str()))
-        .unwrap_or("#f44336"); // Standard error color if not specified
-
-    // Try to get surface colors from theme, fallback to generated ones if not available
-    let surface_hex = theme
-        .get("surface")
-        .and_then(|v| v.as_str())
-        .or_else(|| theme.get("mSurface").and_then(|v| v.as_str()));
-
-    let surface_variant_hex = theme
-        .get("surface_variant")
-        .and_then(|v| v.as_str())
-        .or_else(|| theme.get("mSurfaceVariant").and_then(|v| v.as_str()));
-
-    // Convert hex to HCT for primary
-    let primary_rgb = color::hex_to_rgb(primary_hex)?;
-    let primary_hct = color::rgb_to_hct(primary_rgb.r, primary_rgb.g, primary_rgb.b);
+    let mut theme = theme.clone();
+    let source = theme.primary;
+
+    // Command line and config adjustments win over the theme's own keys
+    let adjustments = options
+        .adjustments
+        .or(PaletteAdjustments::from_theme(&theme)?);
+    if !adjustments.is_identity() {
+        theme = adjustments.apply_to_theme(&theme);
+    }
+    let tone_offset = adjustments.tone_offset.unwrap_or(0.0);
+
+    let mut palette = if let Some(scheme) = options.scheme {
+        // Dynamic schemes only use the primary color as their source. Its hue is
+        // shifted, while the chroma scale and tone offset go to the palettes and
+        // role tones the scheme derives from it
+        let ThemeColor(source_rgb) = source.ok_or("Primary color not found in theme")?;
+        let source_hct = PaletteAdjustments {
+            hue_shift: adjustments.hue_shift,
+            ..Default::default()
+        }
+        .apply(&color::rgb_to_hct(source_rgb.r, source_rgb.g, source_rgb.b));
+        let source_hct = fix_if_disliked(&source_hct, options);
+        generate_scheme_palette(
+            adjustments.apply_to_palettes(scheme.tonal_palettes(&source_hct)),
+            is_dark_mode,
+            tone_offset,
+            options.contrast,
+            options.metric,
+        )
+    } else {
+        generate_theme_palette(&theme, is_dark_mode, options)
+    }?;
+    palette.custom = generate_custom_colors(&theme, &palette, is_dark_mode, tone_offset, options)?;
+    palette.ansi = AnsiPalette::generate(&palette, is_dark_mode, options);
+    for (name, ThemeColor(rgba)) in &theme.ansi {
+        palette.ansi.set(name, color_format_from_rgba(*rgba))?;
+    }
+
+    if crate::log::is_verbose() {
+        eprintln!("Color palette generated successfully");
+    }
+    Ok(palette)
+}
+
+/// Apply the dislike correction to a source color unless the options turn it off
+fn fix_if_disliked(hct: &color::Hct, options: &PaletteOptions) -> color::Hct {
+    if options.fix_disliked {
+        color::fix_if_disliked(hct)
+    } else {
+        hct.clone()
+    }
+}
+
+/// Generate a palette from the colors the theme sets, deriving the missing roles
+fn generate_theme_palette(
+    theme: &ThemeMode,
+    is_dark_mode: bool,
+    options: &PaletteOptions,
+) -> Result<Palette, String> {
+    // Containers and outlines are kept apart from the most elevated surface
+    let highest_surface_tone = if is_dark_mode { 24.0 } else { 87.0 };
+    let contrast_tone = |tone: f64, curve: &ContrastCurve| {
+        scheme::contrast_tone(
+            tone,
+            highest_surface_tone,
+            curve,
+            options.contrast,
+            options.metric,
+        )
+    };
+
+    // On-colors the theme does not set are solved in their background's hue family
+    let theme_color =
+        |color: Option<ThemeColor>| color.map(|ThemeColor(rgba)| color_format_from_rgba(rgba));
+    let on_color = |background: &ColorFormat, dark_tone: f64, light_tone: f64, curve| {
+        let tone = if is_dark_mode { dark_tone } else { light_tone };
+        on_color_format(background, tone, curve, options)
+    };
+
+    let ThemeColor(primary_rgb) = theme.primary.ok_or("Primary color not found in theme")?;
+    // Standard error color if not specified
+    let ThemeColor(error_rgb) = theme
+        .error
+        .unwrap_or(ThemeColor(color::parse_color("#f44336")?));
 
-    // Convert hex to HCT for secondary and tertiary
-    let secondary_rgb = color::hex_to_rgb(secondary_hex)?;
-    let secondary_hct = color::rgb_to_hct(secondary_rgb.r, secondary_rgb.g, secondary_rgb.b);
+    // Convert to HCT for primary
+    let primary_hct = color::rgb_to_hct(primary_rgb.r, primary_rgb.g, primary_rgb.b);
 
-    let tertiary_rgb = color::hex_to_rgb(tertiary_hex)?;
-    let tertiary_hct = color::rgb_to_hct(tertiary_rgb.r, tertiary_rgb.g, tertiary_rgb.b);
+    // Convert to HCT for secondary and tertiary. Missing ones get distinct hues
+    // from the primary color's temperature: its nearest analogous color for
+    // secondary and one further around the wheel for tertiary
+    let temperature = OnceCell::new();
+    let temperature = || temperature.get_or_init(|| color::TemperatureCache::new(&primary_hct));
+    let accent = |color: Option<ThemeColor>, derive: &dyn Fn() -> color::Hct| match color {
+        Some(ThemeColor(rgb)) => {
+            let hct = color::rgb_to_hct(rgb.r, rgb.g, rgb.b);
+            (rgb, hct)
+        }
+        None => {
+            let hct = derive();
+            (hct.to_rgb().with_alpha(primary_rgb.a), hct)
+        }
+    };
+    let (secondary_rgb, secondary_hct) = accent(theme.secondary, &|| {
+        temperature().analogous_colors(3, 12)[0].clone()
+    });
+    let (tertiary_rgb, tertiary_hct) = accent(theme.tertiary, &|| {
+        temperature().analogous_colors(3, 6)[2].clone()
+    });
 
-    let error_rgb = color::hex_to_rgb(error_hex)?;
     let error_hct = color::rgb_to_hct(error_rgb.r, error_rgb.g, error_rgb.b);
 
-    // Create primary colors using HCT
-    let primary = create_color_format(&primary_hct.to_hex())?;
-    let on_primary = if is_dark_mode {
-        // Try to get specific on_primary color, fallback to standard
-        theme
-            .get("on_primary")
-            .and_then(|v| v.as_str())
-            .or_else(|| theme.get("mOnPrimary").and_then(|v| v.as_str()))
-            .map(create_color_format)
-            .unwrap_or_else(|| create_color_format("#ffffff"))
-    } else {
-        theme
-            .get("on_primary")
-            .and_then(|v| v.as_str())
-            .or_else(|| theme.get("mOnPrimary").and_then(|v| v.as_str()))
-            .map(create_color_format)
-            .unwrap_or_else(|| create_color_format("#000000"))
-    }?;
+    // Create primary colors using HCT, keeping the theme's alpha
+    let primary = color_format_from_rgba(primary_hct.to_rgb().with_alpha(primary_rgb.a));
+    let on_primary = theme_color(theme.on_primary)
+        .unwrap_or_else(|| on_color(&primary, 20.0, 100.0, &scheme::CURVE_ON_COLOR));
 
     // Create secondary and tertiary colors
-    let secondary = create_color_format(&secondary_hct.to_hex())?;
-    let on_secondary = if is_dark_mode {
-        theme
-            .get("on_secondary")
-            .and_then(|v| v.as_str())
-            .or_else(|| theme.get("mOnSecondary").and_then(|v| v.as_str()))
-            .map(create_color_format)
-            .unwrap_or_else(|| create_color_format("#ffffff"))
-    } else {
-        theme
-            .get("on_secondary")
-            .and_then(|v| v.as_str())
-            .or_else(|| theme.get("mOnSecondary").and_then(|v| v.as_str()))
-            .map(create_color_format)
-            .unwrap_or_else(|| create_color_format("#000000"))
-    }?;
+    let secondary = color_format_from_rgba(secondary_hct.to_rgb().with_alpha(secondary_rgb.a));
+    let on_secondary = theme_color(theme.on_secondary)
+        .unwrap_or_else(|| on_color(&secondary, 20.0, 100.0, &scheme::CURVE_ON_COLOR));
 
-    let tertiary = create_color_format(&tertiary_hct.to_hex())?;
-    let on_tertiary = if is_dark_mode {
-        theme
-            .get("on_tertiary")
-            .and_then(|v| v.as_str())
-            .or_else(|| theme.get("mOnTertiary").and_then(|v| v.as_str()))
-            .map(create_color_format)
-            .unwrap_or_else(|| create_color_format("#ffffff"))
-    } else {
-        theme
-            .get("on_tertiary")
-            .and_then(|v| v.as_str())
-            .or_else(|| theme.get("mOnTertiary").and_then(|v| v.as_str()))
-            .map(create_color_format)
-            .unwrap_or_else(|| create_color_format("#000000"))
-    }?;
+    let tertiary = color_format_from_rgba(tertiary_hct.to_rgb().with_alpha(tertiary_rgb.a));
+    let on_tertiary = theme_color(theme.on_tertiary)
+        .unwrap_or_else(|| on_color(&tertiary, 20.0, 100.0, &scheme::CURVE_ON_COLOR));
 
-    // Generate container colors (lower chroma, adjusted tone)
+    // Container colors the theme does not set (lower chroma, adjusted tone)
     let primary_container_hct = color::Hct::from_hct(
         primary_hct.h,
-        primary_hct.c * 0.4,                    // Much less chroma
-        if is_dark_mode { 30.0 } else { 90.0 }, // Lower tone for container
+        primary_hct.c * 0.4, // Much less chroma
+        contrast_tone(
+            if is_dark_mode { 30.0 } else { 90.0 }, // Lower tone for container
+            &scheme::CURVE_CONTAINER,
+        ),
     );
-    let primary_container = create_color_format(&primary_container_hct.to_hex())?;
-    let on_primary_container = if is_dark_mode {
-        theme
-            .get("on_primary_container")
-            .and_then(|v| v.as_str())
-            .or_else(|| theme.get("mOnPrimary").and_then(|v| v.as_str())) // Use mOnPrimary as fallback
-            .map(create_color_format)
-            .unwrap_or_else(|| create_color_format("#ffffff"))
-    } else {
-        theme
-            .get("on_primary_container")
-            .and_then(|v| v.as_str())
-            .or_else(|| theme.get("mOnPrimary").and_then(|v| v.as_str())) // Use mOnPrimary as fallback
-            .map(create_color_format)
-            .unwrap_or_else(|| create_color_format("#000000"))
-    }?;
+    let primary_container = theme_color(theme.primary_container)
+        .map(Ok)
+        .unwrap_or_else(|| create_color_format(&primary_container_hct.to_hex()))?;
+    let on_primary_container = theme_color(theme.on_primary_container)
+        .unwrap_or_else(|| on_color(&primary_container, 90.0, 10.0, &scheme::CURVE_ON_COLOR));
 
     let secondary_container_hct = color::Hct::from_hct(
         secondary_hct.h,
         secondary_hct.c * 0.4,
-        if is_dark_mode { 20.0 } else { 95.0 },
+        contrast_tone(
+            if is_dark_mode { 20.0 } else { 95.0 },
+            &scheme::CURVE_CONTAINER,
+        ),
     );
-    let secondary_container = create_color_format(&secondary_container_hct.to_hex())?;
-    let on_secondary_container = if is_dark_mode {
-        create_color_format("#ffffff")?
-    } else {
-        create_color_format("#000000")?
-    };
+    let secondary_container = theme_color(theme.secondary_container)
+        .map(Ok)
+        .unwrap_or_else(|| create_color_format(&secondary_container_hct.to_hex()))?;
+    let on_secondary_container = theme_color(theme.on_secondary_container)
+        .unwrap_or_else(|| on_color(&secondary_container, 90.0, 10.0, &scheme::CURVE_ON_COLOR));
 
     let tertiary_container_hct = color::Hct::from_hct(
         tertiary_hct.h,
         tertiary_hct.c * 0.4,
-        if is_dark_mode { 25.0 } else { 95.0 },
+        contrast_tone(
+            if is_dark_mode { 25.0 } else { 95.0 },
+            &scheme::CURVE_CONTAINER,
+        ),
     );
-    let tertiary_container = create_color_format(&tertiary_container_hct.to_hex())?;
-    let on_tertiary_container = if is_dark_mode {
-        create_color_format("#ffffff")?
-    } else {
-        create_color_format("#000000")?
-    };
+    let tertiary_container = theme_color(theme.tertiary_container)
+        .map(Ok)
+        .unwrap_or_else(|| create_color_format(&tertiary_container_hct.to_hex()))?;
+    let on_tertiary_container = theme_color(theme.on_tertiary_container)
+        .unwrap_or_else(|| on_color(&tertiary_container, 90.0, 10.0, &scheme::CURVE_ON_COLOR));
 
     // Use provided surface colors if available, otherwise generate
-    let (surface, on_surface, surface_hct) = if let Some(hex) = surface_hex {
-        let surface = create_color_format(hex)?;
-        let on_surface = if is_dark_mode {
-            theme
-                .get("on_surface")
-                .and_then(|v| v.as_str())
-                .or_else(|| theme.get("mOnSurface").and_then(|v| v.as_str()))
-                .map(create_color_format)
-                .unwrap_or_else(|| create_color_format("#e0e0e0"))? // Light text on dark surface
-        } else {
-            theme
-                .get("on_surface")
-                .and_then(|v| v.as_str())
-                .or_else(|| theme.get("mOnSurface").and_then(|v| v.as_str()))
-                .map(create_color_format)
-                .unwrap_or_else(|| create_color_format("#1f1f1f"))? // Dark text on light surface
-        };
+    let (surface, on_surface, surface_hct) = if let Some(ThemeColor(surface_rgb)) = theme.surface {
+        let surface = color_format_from_rgba(surface_rgb);
+        let on_surface = theme_color(theme.on_surface)
+            .unwrap_or_else(|| on_color(&surface, 90.0, 10.0, &scheme::CURVE_ON_COLOR));
         // Create HCT from the provided surface color for use in other calculations
-        let surface_rgb = color::hex_to_rgb(hex)?;
         let surface_hct = color::rgb_to_hct(surface_rgb.r, surface_rgb.g, surface_rgb.b);
         (surface, on_surface, surface_hct)
     } else {
@@ -544,35 +1089,26 @@ pub fn generate_palette(
         let surface_tone = if is_dark_mode { 6.0 } else { 98.0 };
         let surface_hct = color::Hct::from_hct(primary_hct.h, 5.0, surface_tone); // Low chroma for surface
         let surface = create_color_format(&surface_hct.to_hex())?;
-        let on_surface = if is_dark_mode {
-            create_color_format("#e0e0e0")? // Light text on dark surface
-        } else {
-            create_color_format("#1f1f1f")? // Dark text on light surface
-        };
+        let on_surface = on_color(&surface, 90.0, 10.0, &scheme::CURVE_ON_COLOR);
         (surface, on_surface, surface_hct)
     };
 
-    let background = surface.clone();
-    let on_background = on_surface.clone();
+    let background = theme_color(theme.background).unwrap_or_else(|| surface.clone());
+    let on_background = theme_color(theme.on_background).unwrap_or_else(|| {
+        if theme.background.is_some() {
+            on_color(&background, 90.0, 10.0, &scheme::CURVE_ON_COLOR)
+        } else {
+            on_surface.clone()
+        }
+    });
 
     // Use provided surface variant color if available, otherwise generate
-    let (surface_variant, on_surface_variant) = if let Some(hex) = surface_variant_hex {
-        let surface_variant = create_color_format(hex)?;
-        let on_surface_variant = if is_dark_mode {
-            theme
-                .get("on_surface_variant")
-                .and_then(|v| v.as_str())
-                .or_else(|| theme.get("mOnSurfaceVariant").and_then(|v| v.as_str()))
-                .map(create_color_format)
-                .unwrap_or_else(|| create_color_format("#c4c4c4"))?
-        } else {
-            theme
-                .get("on_surface_variant")
-                .and_then(|v| v.as_str())
-                .or_else(|| theme.get("mOnSurfaceVariant").and_then(|v| v.as_str()))
-                .map(create_color_format)
-                .unwrap_or_else(|| create_color_format("#49454f"))?
-        };
+    let (surface_variant, on_surface_variant) = if let Some(ThemeColor(rgba)) =
+        theme.surface_variant
+    {
+        let surface_variant = color_format_from_rgba(rgba);
+        let on_surface_variant = theme_color(theme.on_surface_variant)
+            .unwrap_or_else(|| on_color(&surface_variant, 80.0, 30.0, &scheme::CURVE_ON_VARIANT));
         (surface_variant, on_surface_variant)
     } else {
         // Generate surface variant (slightly different hue)
@@ -582,11 +1118,7 @@ pub fn generate_palette(
             if is_dark_mode { 10.0 } else { 94.0 },
         );
         let surface_variant = create_color_format(&surface_variant_hct.to_hex())?;
-        let on_surface_variant = if is_dark_mode {
-            create_color_format("#c4c4c4")?
-        } else {
-            create_color_format("#49454f")?
-        };
+        let on_surface_variant = on_color(&surface_variant, 80.0, 30.0, &scheme::CURVE_ON_VARIANT);
         (surface_variant, on_surface_variant)
     };
 
@@ -602,46 +1134,47 @@ pub fn generate_palette(
     let surface_container_highest_hct =
         color::Hct::from_hct(primary_hct.h, 5.0, if is_dark_mode { 12.0 } else { 87.0 });
 
-    let surface_container_lowest = create_color_format(&surface_container_lowest_hct.to_hex())?;
-    let surface_container_low = create_color_format(&surface_container_low_hct.to_hex())?;
-    let surface_container = create_color_format(&surface_container_hct.to_hex())?;
-    let surface_container_high = create_color_format(&surface_container_high_hct.to_hex())?;
-    let surface_container_highest = create_color_format(&surface_container_highest_hct.to_hex())?;
+    let surface_container_lowest = theme_color(theme.surface_container_lowest)
+        .map(Ok)
+        .unwrap_or_else(|| create_color_format(&surface_container_lowest_hct.to_hex()))?;
+    let surface_container_low = theme_color(theme.surface_container_low)
+        .map(Ok)
+        .unwrap_or_else(|| create_color_format(&surface_container_low_hct.to_hex()))?;
+    let surface_container = theme_color(theme.surface_container)
+        .map(Ok)
+        .unwrap_or_else(|| create_color_format(&surface_container_hct.to_hex()))?;
+    let surface_container_high = theme_color(theme.surface_container_high)
+        .map(Ok)
+        .unwrap_or_else(|| create_color_format(&surface_container_high_hct.to_hex()))?;
+    let surface_container_highest = theme_color(theme.surface_container_highest)
+        .map(Ok)
+        .unwrap_or_else(|| create_color_format(&surface_container_highest_hct.to_hex()))?;
 
     // Fixed accent colors (maintain consistent appearance across themes)
     let primary_fixed_hct = color::Hct::from_hct(primary_hct.h, primary_hct.c * 0.9, 90.0);
     let primary_fixed_dim_hct = color::Hct::from_hct(primary_hct.h, primary_hct.c * 0.7, 75.0);
     let primary_fixed = create_color_format(&primary_fixed_hct.to_hex())?;
     let primary_fixed_dim = create_color_format(&primary_fixed_dim_hct.to_hex())?;
-    let on_primary_fixed = create_color_format("#000000")?;
-    let on_primary_fixed_variant = if is_dark_mode {
-        create_color_format("#9a87ff")? // Based on primary
-    } else {
-        create_color_format("#43389d")? // Based on primary
-    };
+    let on_primary_fixed = on_color(&primary_fixed_dim, 10.0, 10.0, &scheme::CURVE_ON_COLOR);
+    let on_primary_fixed_variant =
+        on_color(&primary_fixed_dim, 30.0, 30.0, &scheme::CURVE_ON_VARIANT);
 
     let secondary_fixed_hct = color::Hct::from_hct(secondary_hct.h, secondary_hct.c * 0.9, 90.0);
     let secondary_fixed_dim_hct =
         color::Hct::from_hct(secondary_hct.h, secondary_hct.c * 0.7, 75.0);
     let secondary_fixed = create_color_format(&secondary_fixed_hct.to_hex())?;
     let secondary_fixed_dim = create_color_format(&secondary_fixed_dim_hct.to_hex())?;
-    let on_secondary_fixed = create_color_format("#000000")?;
-    let on_secondary_fixed_variant = if is_dark_mode {
-        create_color_format("#67daff")? // Based on secondary
-    } else {
-        create_color_format("#006b60")? // Based on secondary
-    };
+    let on_secondary_fixed = on_color(&secondary_fixed_dim, 10.0, 10.0, &scheme::CURVE_ON_COLOR);
+    let on_secondary_fixed_variant =
+        on_color(&secondary_fixed_dim, 30.0, 30.0, &scheme::CURVE_ON_VARIANT);
 
     let tertiary_fixed_hct = color::Hct::from_hct(tertiary_hct.h, tertiary_hct.c * 0.9, 90.0);
     let tertiary_fixed_dim_hct = color::Hct::from_hct(tertiary_hct.h, tertiary_hct.c * 0.7, 75.0);
     let tertiary_fixed = create_color_format(&tertiary_fixed_hct.to_hex())?;
     let tertiary_fixed_dim = create_color_format(&tertiary_fixed_dim_hct.to_hex())?;
-    let on_tertiary_fixed = create_color_format("#000000")?;
-    let on_tertiary_fixed_variant = if is_dark_mode {
-        create_color_format("#f8c26d")? // Based on tertiary
-    } else {
-        create_color_format("#442a51")? // Based on tertiary
-    };
+    let on_tertiary_fixed = on_color(&tertiary_fixed_dim, 10.0, 10.0, &scheme::CURVE_ON_COLOR);
+    let on_tertiary_fixed_variant =
+        on_color(&tertiary_fixed_dim, 30.0, 30.0, &scheme::CURVE_ON_VARIANT);
 
     // Inverse colors
     let inverse_surface_hct = color::Hct::from_hct(
@@ -650,15 +1183,20 @@ pub fn generate_palette(
         if is_dark_mode { 90.0 } else { 20.0 },
     );
     let inverse_surface = create_color_format(&inverse_surface_hct.to_hex())?;
-    let inverse_on_surface = if is_dark_mode {
-        create_color_format("#313031")? // Dark text on light inverse
-    } else {
-        create_color_format("#e3e1e3")? // Light text on dark inverse
-    };
-    let inverse_primary = if is_dark_mode {
-        create_color_format("#6200ee")? // Light theme primary for dark theme inverse
-    } else {
-        create_color_format("#bb86fc")? // Dark theme primary for light theme inverse
+    let inverse_on_surface = on_color(&inverse_surface, 20.0, 95.0, &scheme::CURVE_ON_COLOR);
+    let inverse_primary = {
+        let tone = scheme::contrast_tone(
+            if is_dark_mode { 40.0 } else { 80.0 },
+            inverse_surface_hct.t,
+            &scheme::CURVE_ACCENT,
+            options.contrast,
+            options.metric,
+        );
+        color_format_from_rgba(
+            color::Hct::from_hct(primary_hct.h, primary_hct.c, tone)
+                .to_rgb()
+                .into(),
+        )
     };
 
     // Bright and dim surface colors
@@ -676,60 +1214,71 @@ pub fn generate_palette(
     let surface_bright = create_color_format(&surface_bright_hct.to_hex())?;
 
     // Error colors
-    let error = create_color_format(&error_hct.to_hex())?;
-    let on_error = if is_dark_mode {
-        theme
-            .get("on_error")
-            .and_then(|v| v.as_str())
-            .or_else(|| theme.get("mOnError").and_then(|v| v.as_str()))
-            .map(create_color_format)
-            .unwrap_or_else(|| create_color_format("#410002"))? // Dark text on light error
-    } else {
-        theme
-            .get("on_error")
-            .and_then(|v| v.as_str())
-            .or_else(|| theme.get("mOnError").and_then(|v| v.as_str()))
-            .map(create_color_format)
-            .unwrap_or_else(|| create_color_format("#ffffff"))? // Light text on dark error
-    };
-
-    let error_container_hct =
-        color::Hct::from_hct(error_hct.h, 30.0, if is_dark_mode { 30.0 } else { 95.0 });
-    let error_container = create_color_format(&error_container_hct.to_hex())?;
-    let on_error_container = if is_dark_mode {
-        create_color_format("#ffdad6")? // Light text on dark error container
-    } else {
-        create_color_format("#410002")? // Dark text on light error container
-    };
+    let error = color_format_from_rgba(error_hct.to_rgb().with_alpha(error_rgb.a));
+    let on_error = theme_color(theme.on_error)
+        .unwrap_or_else(|| on_color(&error, 20.0, 100.0, &scheme::CURVE_ON_COLOR));
+
+    let error_container_hct = color::Hct::from_hct(
+        error_hct.h,
+        30.0,
+        contrast_tone(
+            if is_dark_mode { 30.0 } else { 95.0 },
+            &scheme::CURVE_CONTAINER,
+        ),
+    );
+    let error_container = theme_color(theme.error_container)
+        .map(Ok)
+        .unwrap_or_else(|| create_color_format(&error_container_hct.to_hex()))?;
+    let on_error_container = theme_color(theme.on_error_container)
+        .unwrap_or_else(|| on_color(&error_container, 90.0, 10.0, &scheme::CURVE_ON_COLOR));
 
     // Outline colors - try to use mOutline if available
-    let outline = theme
-        .get("outline")
-        .and_then(|v| v.as_str())
-        .or_else(|| theme.get("mOutline").and_then(|v| v.as_str()))
-        .map(create_color_format)
-        .unwrap_or_else(|| {
-            let outline_hct =
-                color::Hct::from_hct(surface_hct.h, 10.0, if is_dark_mode { 60.0 } else { 50.0 });
-            create_color_format(&outline_hct.to_hex())
-        })?;
+    let outline = theme_color(theme.outline).unwrap_or_else(|| {
+        let outline_hct = color::Hct::from_hct(
+            surface_hct.h,
+            10.0,
+            contrast_tone(
+                if is_dark_mode { 60.0 } else { 50.0 },
+                &scheme::CURVE_OUTLINE,
+            ),
+        );
+        color_format_from_rgba(outline_hct.to_rgb().into())
+    });
 
-    let outline_variant = {
-        let outline_variant_hct =
-            color::Hct::from_hct(surface_hct.h, 5.0, if is_dark_mode { 30.0 } else { 80.0 });
+    let outline_variant = if let Some(outline_variant) = theme_color(theme.outline_variant) {
+        outline_variant
+    } else {
+        let outline_variant_hct = color::Hct::from_hct(
+            surface_hct.h,
+            5.0,
+            contrast_tone(
+                if is_dark_mode { 30.0 } else { 80.0 },
+                &scheme::CURVE_CONTAINER,
+            ),
+        );
         create_color_format(&outline_variant_hct.to_hex())?
     };
 
     // Other colors
-    let shadow = theme
-        .get("shadow")
-        .and_then(|v| v.as_str())
-        .or_else(|| theme.get("mShadow").and_then(|v| v.as_str()))
-        .map(create_color_format)
+    let shadow = theme_color(theme.shadow)
+        .map(Ok)
         .unwrap_or_else(|| create_color_format("#000000"))?; // Use mShadow if available, otherwise black
 
     let scrim = create_color_format("#000000")?; // Always black
 
+    // Tonal palettes for templates that need intermediate tones
+    let palettes = TonalPalettes {
+        primary: color::TonalPalette::from_hue_and_chroma(primary_hct.h, primary_hct.c),
+        secondary: color::TonalPalette::from_hue_and_chroma(secondary_hct.h, secondary_hct.c),
+        tertiary: color::TonalPalette::from_hue_and_chroma(tertiary_hct.h, tertiary_hct.c),
+        neutral: color::TonalPalette::from_hue_and_chroma(surface_hct.h, surface_hct.c),
+        neutral_variant: color::TonalPalette::from_hue_and_chroma(
+            surface_hct.h,
+            surface_hct.c * 2.0,
+        ),
+        error: color::TonalPalette::from_hue_and_chroma(error_hct.h, error_hct.c),
+    };
+
     let palette = Palette {
         primary: ColorEntry { default: primary },
         on_primary: ColorEntry {
@@ -857,131 +1406,279 @@ pub fn generate_palette(
         },
         shadow: ColorEntry { default: shadow },
         scrim: ColorEntry { default: scrim },
+        custom: Vec::new(),
+        ansi: AnsiPalette::default(),
+        palettes,
     };
 
-    if crate::log::is_verbose() {
-        eprintln!("Color palette generated successfully");
-    }
     Ok(palette)
 }
 
-/// Generate a harmonious color based on the source color with a hue shift
-fn generate_harmonious_color(
-    source_hex: &str,
-    hue_shift: f64,
-    saturation_change: f64,
-) -> Result<ColorFormat, String> {
-    let source_rgb = color::hex_to_rgb(source_hex)?;
-    let source_hsl = color::rgb_to_hsl(
-        source_rgb.r as f64,
-        source_rgb.g as f64,
-        source_rgb.b as f64,
+/// An on-color in the hue and chroma of its background
+///
+/// Starts from the MD3 tone of the role and moves it only as far as needed to
+/// meet the contrast curve against the background's actual tone.
+fn on_color_format(
+    background: &ColorFormat,
+    tone: f64,
+    curve: &ContrastCurve,
+    options: &PaletteOptions,
+) -> ColorFormat {
+    let rgb = background.rgb();
+    let background_hct = color::rgb_to_hct(rgb.r, rgb.g, rgb.b);
+    let palette = color::TonalPalette::from_hue_and_chroma(background_hct.h, background_hct.c);
+    let tone = scheme::contrast_tone(
+        tone,
+        background_hct.t,
+        curve,
+        options.contrast,
+        options.metric,
     );
+    tone_color_format(&palette, tone)
+}
 
-    // Apply hue shift (keeping within 0-360 range)
-    let new_hue = (source_hsl.h + hue_shift) % 360.0;
-    let new_hue = if new_hue < 0.0 {
-        new_hue + 360.0
-    } else {
-        new_hue
-    };
-
-    // Apply saturation change (keeping within 0-100 range)
-    let new_saturation = color::clamp(source_hsl.s + saturation_change, 0.0, 100.0);
-
-    let new_rgb = color::hsl_to_rgb(new_hue, new_saturation, source_hsl.l);
-    let new_hex = color::rgb_to_hex(new_rgb.r as f64, new_rgb.g as f64, new_rgb.b as f64);
+/// Tones of the four roles of an accent color (primary, secondary, tertiary, error)
+struct AccentTones {
+    color: f64,
+    on_color: f64,
+    container: f64,
+    on_container: f64,
+}
 
-    create_color_format(&new_hex)
+/// MD3 tone of surface_container_highest, the most elevated surface, which
+/// accents and containers are checked against
+fn highest_surface_tone(is_dark_mode: bool, tone_offset: f64) -> f64 {
+    let tone = if is_dark_mode { 22.0 } else { 90.0 };
+    (tone + tone_offset).clamp(0.0, 100.0)
 }
 
-/// Generate a color with appropriate contrast based on the background
-fn generate_contrast_color(
-    background_hex: &str,
+/// MD3 tones of the accent roles, offset and then moved as needed to meet the
+/// contrast level
+fn accent_tones(
     is_dark_mode: bool,
-) -> Result<ColorFormat, String> {
-    let luminance = color::get_luminance(background_hex)?;
-
-    // For dark mode, if background is dark, use light text; if background is light, use dark text
-    // For light mode, if background is light, use dark text; if background is dark, use light text
-    if (is_dark_mode && luminance < 0.5) || (!is_dark_mode && luminance > 0.5) {
-        // Background is dark in dark mode or light in light mode
-        // Use opposite text color for contrast
-        if color::get_contrast_ratio(background_hex, "#ffffff")? >= 4.5 {
-            create_color_format("#ffffff")
-        } else {
-            // If white doesn't provide enough contrast, use a light gray
-            create_color_format("#e6e1e5")
-        }
-    } else {
-        // Background is light in dark mode or dark in light mode
-        // Use opposite text color for contrast
-        if color::get_contrast_ratio(background_hex, "#000000")? >= 4.5 {
-            create_color_format("#000000")
-        } else {
-            // If black doesn't provide enough contrast, use a dark gray
-            create_color_format("#1c1b1f")
-        }
+    tone_offset: f64,
+    contrast: ContrastLevel,
+    metric: ContrastMetric,
+) -> AccentTones {
+    let tone = |dark_tone: f64, light_tone: f64| {
+        let tone = if is_dark_mode { dark_tone } else { light_tone };
+        (tone + tone_offset).clamp(0.0, 100.0)
+    };
+    let solve = |tone: f64, background_tone: f64, curve: &ContrastCurve| {
+        scheme::contrast_tone(tone, background_tone, curve, contrast, metric)
+    };
+
+    // Accents are checked against the most elevated surface, and containers
+    // are kept at tones their on-colors can reach
+    let highest_surface = highest_surface_tone(is_dark_mode, tone_offset);
+    let color = solve(tone(80.0, 40.0), highest_surface, &scheme::CURVE_ACCENT);
+    let container = scheme::readable_container_tone(
+        solve(tone(30.0, 90.0), highest_surface, &scheme::CURVE_CONTAINER),
+        highest_surface,
+        metric.target(scheme::CURVE_ON_COLOR.get(contrast)),
+        metric,
+    );
+    AccentTones {
+        color,
+        on_color: solve(tone(20.0, 100.0), color, &scheme::CURVE_ON_COLOR),
+        container,
+        on_container: solve(tone(90.0, 10.0), container, &scheme::CURVE_ON_COLOR),
     }
 }
 
-/// Generate a container color based on the source color and theme
-fn generate_container_color(source_hex: &str, is_dark_mode: bool) -> Result<String, String> {
-    let source_rgb = color::hex_to_rgb(source_hex)?;
-    let source_hsl = color::rgb_to_hsl(
-        source_rgb.r as f64,
-        source_rgb.g as f64,
-        source_rgb.b as f64,
-    );
+/// Generate a palette by taking every role from its tonal palette at the MD3 tone
+/// plus the tone offset, moved as needed to meet the contrast level
+fn generate_scheme_palette(
+    palettes: TonalPalettes,
+    is_dark_mode: bool,
+    tone_offset: f64,
+    contrast: ContrastLevel,
+    metric: ContrastMetric,
+) -> Result<Palette, String> {
+    let tone = |dark_tone: f64, light_tone: f64| {
+        let tone = if is_dark_mode { dark_tone } else { light_tone };
+        (tone + tone_offset).clamp(0.0, 100.0)
+    };
+    let solve = |tone: f64, background_tone: f64, curve: &ContrastCurve| {
+        scheme::contrast_tone(tone, background_tone, curve, contrast, metric)
+    };
+
+    // Surfaces keep their tones; accents are checked against the most elevated one
+    let surface = tone(6.0, 98.0);
+    let highest_surface = highest_surface_tone(is_dark_mode, tone_offset);
+    let inverse_surface = tone(90.0, 20.0);
+
+    let accent = accent_tones(is_dark_mode, tone_offset, contrast, metric);
 
-    // Container colors are typically more muted and darker/lighter than the source
-    let new_lightness = if is_dark_mode {
-        color::clamp(source_hsl.l - 20.0, 0.0, 100.0) // Darker in dark mode
+    // Fixed roles are the same light tones in both modes. Only raised contrast
+    // moves them, to the dark pair, when the light ones are too close to the surface
+    let fixed_target = metric.target(scheme::CURVE_CONTAINER.get(contrast));
+    let (fixed, fixed_dim) = if contrast.value() > 0.0
+        && metric.contrast_of_tones(tone(80.0, 80.0), highest_surface) < fixed_target
+    {
+        (tone(40.0, 40.0), tone(30.0, 30.0))
     } else {
-        color::clamp(source_hsl.l + 15.0, 0.0, 100.0) // Lighter in light mode
+        (tone(90.0, 90.0), tone(80.0, 80.0))
     };
+    let on_fixed = solve(tone(10.0, 10.0), fixed_dim, &scheme::CURVE_ON_COLOR);
+    let on_fixed_variant = solve(tone(30.0, 30.0), fixed_dim, &scheme::CURVE_ON_VARIANT);
+    let on_surface = solve(tone(90.0, 10.0), highest_surface, &scheme::CURVE_ON_COLOR);
 
-    let new_rgb = color::hsl_to_rgb(source_hsl.h, source_hsl.s, new_lightness);
-    Ok(color::rgb_to_hex(
-        new_rgb.r as f64,
-        new_rgb.g as f64,
-        new_rgb.b as f64,
-    ))
+    let entry = |palette: &color::TonalPalette, tone: f64| ColorEntry {
+        default: tone_color_format(palette, tone),
+    };
+    let p = &palettes;
+
+    let palette = Palette {
+        primary: entry(&p.primary, accent.color),
+        on_primary: entry(&p.primary, accent.on_color),
+        primary_container: entry(&p.primary, accent.container),
+        on_primary_container: entry(&p.primary, accent.on_container),
+        primary_fixed: entry(&p.primary, fixed),
+        primary_fixed_dim: entry(&p.primary, fixed_dim),
+        on_primary_fixed: entry(&p.primary, on_fixed),
+        on_primary_fixed_variant: entry(&p.primary, on_fixed_variant),
+        secondary: entry(&p.secondary, accent.color),
+        on_secondary: entry(&p.secondary, accent.on_color),
+        secondary_container: entry(&p.secondary, accent.container),
+        on_secondary_container: entry(&p.secondary, accent.on_container),
+        secondary_fixed: entry(&p.secondary, fixed),
+        secondary_fixed_dim: entry(&p.secondary, fixed_dim),
+        on_secondary_fixed: entry(&p.secondary, on_fixed),
+        on_secondary_fixed_variant: entry(&p.secondary, on_fixed_variant),
+        tertiary: entry(&p.tertiary, accent.color),
+        on_tertiary: entry(&p.tertiary, accent.on_color),
+        tertiary_container: entry(&p.tertiary, accent.container),
+        on_tertiary_container: entry(&p.tertiary, accent.on_container),
+        tertiary_fixed: entry(&p.tertiary, fixed),
+        tertiary_fixed_dim: entry(&p.tertiary, fixed_dim),
+        on_tertiary_fixed: entry(&p.tertiary, on_fixed),
+        on_tertiary_fixed_variant: entry(&p.tertiary, on_fixed_variant),
+        error: entry(&p.error, accent.color),
+        on_error: entry(&p.error, accent.on_color),
+        error_container: entry(&p.error, accent.container),
+        on_error_container: entry(&p.error, accent.on_container),
+        background: entry(&p.neutral, surface),
+        on_background: entry(&p.neutral, on_surface),
+        surface: entry(&p.neutral, surface),
+        on_surface: entry(&p.neutral, on_surface),
+        surface_variant: entry(&p.neutral_variant, tone(30.0, 90.0)),
+        on_surface_variant: entry(
+            &p.neutral_variant,
+            solve(tone(80.0, 30.0), highest_surface, &scheme::CURVE_ON_VARIANT),
+        ),
+        surface_container_lowest: entry(&p.neutral, tone(4.0, 100.0)),
+        surface_container_low: entry(&p.neutral, tone(10.0, 96.0)),
+        surface_container: entry(&p.neutral, tone(12.0, 94.0)),
+        surface_container_high: entry(&p.neutral, tone(17.0, 92.0)),
+        surface_container_highest: entry(&p.neutral, highest_surface),
+        inverse_surface: entry(&p.neutral, inverse_surface),
+        inverse_on_surface: entry(
+            &p.neutral,
+            solve(tone(20.0, 95.0), inverse_surface, &scheme::CURVE_ON_COLOR),
+        ),
+        inverse_primary: entry(
+            &p.primary,
+            solve(tone(40.0, 80.0), inverse_surface, &scheme::CURVE_ACCENT),
+        ),
+        surface_dim: entry(&p.neutral, tone(6.0, 87.0)),
+        surface_bright: entry(&p.neutral, tone(24.0, 98.0)),
+        outline: entry(
+            &p.neutral_variant,
+            solve(tone(60.0, 50.0), highest_surface, &scheme::CURVE_OUTLINE),
+        ),
+        outline_variant: entry(
+            &p.neutral_variant,
+            solve(tone(30.0, 80.0), highest_surface, &scheme::CURVE_CONTAINER),
+        ),
+        shadow: entry(&p.neutral, 0.0),
+        scrim: entry(&p.neutral, 0.0),
+        custom: Vec::new(),
+        ansi: AnsiPalette::default(),
+        palettes,
+    };
+
+    Ok(palette)
 }
 
-/// Adjust the lightness of a color by a given amount
-fn adjust_lightness(hexcolor: &str, amount: f64) -> Result<String, String> {
-    let rgb = color::hex_to_rgb(hexcolor)?;
-    let hsl = color::rgb_to_hsl(rgb.r as f64, rgb.g as f64, rgb.b as f64);
-    let new_l = color::clamp(hsl.l + amount, 0.0, 100.0);
-    let new_rgb = color::hsl_to_rgb(hsl.h, hsl.s, new_l);
-    Ok(color::rgb_to_hex(
-        new_rgb.r as f64,
-        new_rgb.g as f64,
-        new_rgb.b as f64,
-    ))
+/// Read the theme's custom colors as (name, color, harmonize) entries, checking their names
+fn custom_color_inputs(theme: &ThemeMode) -> Result<Vec<(String, color::Rgba, bool)>, String> {
+    theme
+        .custom
+        .iter()
+        .map(|(name, entry)| {
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(format!(
+                    "Custom color name '{}' may only contain letters, digits and underscores",
+                    name
+                ));
+            }
+            Ok((name.clone(), entry.color.0, entry.harmonize))
+        })
+        .collect()
 }
 
-/// Adjust both lightness and saturation of a color
-fn adjust_lightness_and_saturation(hexcolor: &str, la: f64, sa: f64) -> Result<String, String> {
-    let rgb = color::hex_to_rgb(hexcolor)?;
-    let hsl = color::rgb_to_hsl(rgb.r as f64, rgb.g as f64, rgb.b as f64);
-    let new_l = color::clamp(hsl.l + la, 0.0, 100.0);
-    let new_s = color::clamp(hsl.s + sa, 0.0, 100.0);
-    let new_rgb = color::hsl_to_rgb(hsl.h, new_s, new_l);
-    Ok(color::rgb_to_hex(
-        new_rgb.r as f64,
-        new_rgb.g as f64,
-        new_rgb.b as f64,
-    ))
+/// Generate the roles of the theme's custom colors
+///
+/// Each color gets its own tonal palette, with its hue rotated toward the
+/// primary hue when harmonized, and uses the same tones as the accent roles.
+fn generate_custom_colors(
+    theme: &ThemeMode,
+    palette: &Palette,
+    is_dark_mode: bool,
+    tone_offset: f64,
+    options: &PaletteOptions,
+) -> Result<Vec<CustomColor>, String> {
+    let inputs = custom_color_inputs(theme)?;
+    if inputs.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let built_in = palette.roles();
+    let tones = accent_tones(is_dark_mode, tone_offset, options.contrast, options.metric);
+    let primary = palette.palettes.primary.hct(50.0);
+
+    inputs
+        .into_iter()
+        .map(|(name, rgba, harmonize)| {
+            if let Some(role) = custom_role_names(&name)
+                .into_iter()
+                .find(|role| built_in.iter().any(|(built_in, _)| built_in == role))
+            {
+                return Err(format!(
+                    "Custom color '{}' conflicts with the built-in role '{}'",
+                    name, role
+                ));
+            }
+
+            let mut hct = color::rgb_to_hct(rgba.r, rgba.g, rgba.b);
+            if harmonize {
+                hct = color::harmonize(&hct, &primary);
+            }
+            let tonal_palette = color::TonalPalette::from_hue_and_chroma(hct.h, hct.c);
+            let entry = |tone: f64| ColorEntry {
+                default: tone_color_format(&tonal_palette, tone),
+            };
+
+            Ok(CustomColor {
+                color: entry(tones.color),
+                on_color: entry(tones.on_color),
+                color_container: entry(tones.container),
+                on_color_container: entry(tones.on_container),
+                palette: tonal_palette,
+                name,
+            })
+        })
+        .collect()
 }
 
-/// Process theme - main function to generate theme from JSON and template
+/// Process theme - main function to generate theme from a theme file and template
 pub fn process_theme(
     theme_path: &str,
     template_path: &str,
     output_path: &str,
     mode: &str,
+    options: &PaletteOptions,
 ) -> Result<(), String> {
     if crate::log::is_verbose() {
         eprintln!("Starting theme generation: mode={}", mode);
@@ -991,19 +1688,31 @@ pub fn process_theme(
     if !Path::new(theme_path).exists() {
         return Err(format!("Theme file '{}' does not exist.", theme_path));
     }
+
+    // Read the theme file
+    let theme_all = Theme::from_value(&load_theme(theme_path)?)?;
+    process_theme_data(&theme_all, template_path, output_path, mode, options)
+}
+
+/// Generate a theme from already loaded theme data and a template
+pub fn process_theme_data(
+    theme_all: &Theme,
+    template_path: &str,
+    output_path: &str,
+    mode: &str,
+    options: &PaletteOptions,
+) -> Result<(), String> {
     if !Path::new(template_path).exists() {
         return Err(format!("Template file '{}' does not exist.", template_path));
     }
 
-    // Read JSON theme
-    let theme_all = load_theme(theme_path)?;
-    let (theme, effective_mode) = select_theme_mode(&theme_all, mode)?;
+    let (theme, effective_mode) = theme_all.mode(mode)?;
 
     // Generate palette
     if crate::log::is_verbose() {
         eprintln!("Generating color palette...");
     }
-    let palette = generate_palette(&theme, effective_mode == "dark", false)?;
+    let palette = generate_palette_with_options(&theme, effective_mode == "dark", false, options)?;
     if crate::log::is_verbose() {
         eprintln!("Color palette generated successfully");
     }
@@ -0,0 +1,298 @@
+// Typed model of theme files
+// Themes are deserialized with serde so that unknown keys and invalid colors
+// are reported with their path in the file, e.g. `dark.on_primry: unknown key`
+
+use crate::color::{self, Rgba};
+use serde::de::{self, DeserializeOwned, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A color written in any CSS syntax the color parser accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThemeColor(pub Rgba);
+
+impl<'de> Deserialize<'de> for ThemeColor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ColorVisitor;
+
+        impl Visitor<'_> for ColorVisitor {
+            type Value = ThemeColor;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a color")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<ThemeColor, E> {
+                color::parse_color(value).map(ThemeColor).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(ColorVisitor)
+    }
+}
+
+/// An entry of a theme's `custom` object: a color, or `{ "hex": ..., "harmonize": ... }`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CustomColorEntry {
+    pub color: ThemeColor,
+    /// Shift the hue toward the primary color (defaults to true)
+    pub harmonize: bool,
+}
+
+impl<'de> Deserialize<'de> for CustomColorEntry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Fields {
+            hex: ThemeColor,
+            harmonize: Option<bool>,
+        }
+
+        struct EntryVisitor;
+
+        impl<'de> Visitor<'de> for EntryVisitor {
+            type Value = CustomColorEntry;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a color or an object with 'hex' and 'harmonize'")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<CustomColorEntry, E> {
+                Ok(CustomColorEntry {
+                    color: ThemeColor::deserialize(de::value::StrDeserializer::new(value))?,
+                    harmonize: true,
+                })
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<CustomColorEntry, A::Error> {
+                let fields = Fields::deserialize(de::value::MapAccessDeserializer::new(map))?;
+                Ok(CustomColorEntry {
+                    color: fields.hex,
+                    harmonize: fields.harmonize.unwrap_or(true),
+                })
+            }
+        }
+
+        deserializer.deserialize_any(EntryVisitor)
+    }
+}
+
+/// The colors and settings of one mode of a theme
+///
+/// Roles that are not set are derived from the others. Keys with an `m`
+/// prefix (`mPrimary`, `mOnSurface`) are accepted as aliases.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeMode {
+    /// Theme whose mode of the same name this one is merged over
+    pub extends: Option<String>,
+
+    #[serde(alias = "mPrimary")]
+    pub primary: Option<ThemeColor>,
+    #[serde(alias = "mOnPrimary")]
+    pub on_primary: Option<ThemeColor>,
+    #[serde(alias = "mPrimaryContainer")]
+    pub primary_container: Option<ThemeColor>,
+    #[serde(alias = "mOnPrimaryContainer")]
+    pub on_primary_container: Option<ThemeColor>,
+    #[serde(alias = "mSecondary")]
+    pub secondary: Option<ThemeColor>,
+    #[serde(alias = "mOnSecondary")]
+    pub on_secondary: Option<ThemeColor>,
+    #[serde(alias = "mSecondaryContainer")]
+    pub secondary_container: Option<ThemeColor>,
+    #[serde(alias = "mOnSecondaryContainer")]
+    pub on_secondary_container: Option<ThemeColor>,
+    #[serde(alias = "mTertiary")]
+    pub tertiary: Option<ThemeColor>,
+    #[serde(alias = "mOnTertiary")]
+    pub on_tertiary: Option<ThemeColor>,
+    #[serde(alias = "mTertiaryContainer")]
+    pub tertiary_container: Option<ThemeColor>,
+    #[serde(alias = "mOnTertiaryContainer")]
+    pub on_tertiary_container: Option<ThemeColor>,
+    #[serde(alias = "mError")]
+    pub error: Option<ThemeColor>,
+    #[serde(alias = "mOnError")]
+    pub on_error: Option<ThemeColor>,
+    #[serde(alias = "mErrorContainer")]
+    pub error_container: Option<ThemeColor>,
+    #[serde(alias = "mOnErrorContainer")]
+    pub on_error_container: Option<ThemeColor>,
+    #[serde(alias = "mSurface")]
+    pub surface: Option<ThemeColor>,
+    #[serde(alias = "mOnSurface")]
+    pub on_surface: Option<ThemeColor>,
+    #[serde(alias = "mSurfaceVariant")]
+    pub surface_variant: Option<ThemeColor>,
+    #[serde(alias = "mOnSurfaceVariant")]
+    pub on_surface_variant: Option<ThemeColor>,
+    #[serde(alias = "mSurfaceContainerLowest")]
+    pub surface_container_lowest: Option<ThemeColor>,
+    #[serde(alias = "mSurfaceContainerLow")]
+    pub surface_container_low: Option<ThemeColor>,
+    #[serde(alias = "mSurfaceContainer")]
+    pub surface_container: Option<ThemeColor>,
+    #[serde(alias = "mSurfaceContainerHigh")]
+    pub surface_container_high: Option<ThemeColor>,
+    #[serde(alias = "mSurfaceContainerHighest")]
+    pub surface_container_highest: Option<ThemeColor>,
+    /// Defaults to the surface color
+    pub background: Option<ThemeColor>,
+    pub on_background: Option<ThemeColor>,
+    #[serde(alias = "mOutline")]
+    pub outline: Option<ThemeColor>,
+    #[serde(alias = "mOutlineVariant")]
+    pub outline_variant: Option<ThemeColor>,
+    #[serde(alias = "mShadow")]
+    pub shadow: Option<ThemeColor>,
+
+    /// Named colors beyond the MD3 roles
+    #[serde(default)]
+    pub custom: BTreeMap<String, CustomColorEntry>,
+
+    /// Terminal colors by name or index (`red`, `color9`), replacing the generated ones
+    #[serde(default)]
+    pub ansi: BTreeMap<String, ThemeColor>,
+
+    /// Degrees added to every hue
+    pub hue_shift: Option<f64>,
+    /// Factor every chroma is multiplied by
+    pub chroma_scale: Option<f64>,
+    /// Tones added to every tone
+    pub tone_offset: Option<f64>,
+}
+
+impl ThemeMode {
+    /// Deserialize a theme mode, reporting errors with their path in the mode
+    pub fn from_value(value: &Value) -> Result<ThemeMode, String> {
+        from_value(value)
+    }
+
+    /// Every color the mode sets, custom colors included
+    pub fn colors_mut(&mut self) -> impl Iterator<Item = &mut ThemeColor> {
+        [
+            &mut self.primary,
+            &mut self.on_primary,
+            &mut self.primary_container,
+            &mut self.on_primary_container,
+            &mut self.secondary,
+            &mut self.on_secondary,
+            &mut self.secondary_container,
+            &mut self.on_secondary_container,
+            &mut self.tertiary,
+            &mut self.on_tertiary,
+            &mut self.tertiary_container,
+            &mut self.on_tertiary_container,
+            &mut self.error,
+            &mut self.on_error,
+            &mut self.error_container,
+            &mut self.on_error_container,
+            &mut self.surface,
+            &mut self.on_surface,
+            &mut self.surface_variant,
+            &mut self.on_surface_variant,
+            &mut self.surface_container_lowest,
+            &mut self.surface_container_low,
+            &mut self.surface_container,
+            &mut self.surface_container_high,
+            &mut self.surface_container_highest,
+            &mut self.background,
+            &mut self.on_background,
+            &mut self.outline,
+            &mut self.outline_variant,
+            &mut self.shadow,
+        ]
+        .into_iter()
+        .flatten()
+        .chain(self.custom.values_mut().map(|entry| &mut entry.color))
+        .chain(self.ansi.values_mut())
+    }
+}
+
+/// A theme file: a mode per appearance, plus settings shared by both
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Theme {
+    /// Theme this one is merged over, by name or path
+    pub extends: Option<String>,
+
+    pub dark: Option<ThemeMode>,
+    pub light: Option<ThemeMode>,
+
+    /// Custom colors for both modes; a mode's own entries take precedence
+    #[serde(default)]
+    pub custom: BTreeMap<String, CustomColorEntry>,
+
+    /// Adjustments for both modes, unless a mode sets its own
+    pub hue_shift: Option<f64>,
+    pub chroma_scale: Option<f64>,
+    pub tone_offset: Option<f64>,
+}
+
+impl Theme {
+    /// Deserialize a theme, reporting errors with their path, e.g. `dark.primary`
+    pub fn from_value(value: &Value) -> Result<Theme, String> {
+        from_value(value)
+    }
+
+    /// The mode to generate a palette from, and the name of the mode used
+    ///
+    /// A theme with a single mode, such as an imported base16 scheme, uses it
+    /// for both. The theme's shared custom colors and adjustments fill in what
+    /// the mode does not set.
+    pub fn mode(&self, mode: &str) -> Result<(ThemeMode, String), String> {
+        let selected = match mode {
+            "dark" => self.dark.as_ref(),
+            "light" => self.light.as_ref(),
+            _ => None,
+        };
+        let (mut theme_mode, effective_mode) = match selected {
+            Some(theme_mode) => (theme_mode.clone(), mode.to_string()),
+            None => {
+                let Some((theme_mode, fallback)) = self
+                    .dark
+                    .as_ref()
+                    .map(|dark| (dark, "dark"))
+                    .or_else(|| self.light.as_ref().map(|light| (light, "light")))
+                else {
+                    return Err(
+                        "Error: 'dark' mode not available in theme.json and requested mode not found."
+                            .to_string(),
+                    );
+                };
+                eprintln!(
+                    "Mode '{}' not found in theme.json. Using '{}'.",
+                    mode, fallback
+                );
+                (theme_mode.clone(), fallback.to_string())
+            }
+        };
+
+        for (name, entry) in &self.custom {
+            theme_mode.custom.entry(name.clone()).or_insert(*entry);
+        }
+        theme_mode.hue_shift = theme_mode.hue_shift.or(self.hue_shift);
+        theme_mode.chroma_scale = theme_mode.chroma_scale.or(self.chroma_scale);
+        theme_mode.tone_offset = theme_mode.tone_offset.or(self.tone_offset);
+        Ok((theme_mode, effective_mode))
+    }
+}
+
+fn from_value<T: DeserializeOwned>(value: &Value) -> Result<T, String> {
+    serde_path_to_error::deserialize(value).map_err(|e| {
+        let message = e.inner().to_string();
+        // serde lists every known field; the path already names the unknown one
+        let message = if message.starts_with("unknown field") {
+            "unknown key".to_string()
+        } else {
+            message
+        };
+        match e.path().to_string().as_str() {
+            "." => message,
+            path => format!("{}: {}", path, message),
+        }
+    })
+}
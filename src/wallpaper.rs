@@ -0,0 +1,51 @@
+// Extract a theme's source color from a wallpaper image
+
+use crate::color::{self, Rgb};
+use crate::{quantize, score};
+use std::path::Path;
+
+/// Longest side images are scaled down to before quantizing
+const MAX_IMAGE_SIZE: u32 = 128;
+/// Number of colors the image is reduced to before scoring
+const MAX_COLORS: usize = 128;
+
+/// Load an image and return the color best suited as a theme's source color
+pub fn source_color_from_image(path: &str) -> Result<Rgb, String> {
+    let path = Path::new(path);
+    let image =
+        image::open(path).map_err(|e| format!("Failed to load image {}: {}", path.display(), e))?;
+
+    let image = image.thumbnail(MAX_IMAGE_SIZE, MAX_IMAGE_SIZE).to_rgba8();
+    let pixels: Vec<Rgb> = image
+        .pixels()
+        .filter(|pixel| pixel[3] == 255) // Skip transparent pixels
+        .map(|pixel| Rgb {
+            r: pixel[0],
+            g: pixel[1],
+            b: pixel[2],
+        })
+        .collect();
+    if pixels.is_empty() {
+        return Err(format!(
+            "Image {} has no opaque pixels to take a color from",
+            path.display()
+        ));
+    }
+
+    Ok(source_color_from_pixels(&pixels))
+}
+
+/// The color best suited as a theme's source color among a list of pixels
+pub fn source_color_from_pixels(pixels: &[Rgb]) -> Rgb {
+    let population = quantize::quantize(pixels, MAX_COLORS);
+    score::score(&population, 1)
+        .into_iter()
+        .next()
+        .unwrap_or(score::FALLBACK_COLOR)
+}
+
+/// Load an image and return its source color as hex
+pub fn source_hex_from_image(path: &str) -> Result<String, String> {
+    let rgb = source_color_from_image(path)?;
+    Ok(color::rgb_to_hex(rgb.r as f64, rgb.g as f64, rgb.b as f64))
+}
@@ -1,9 +1,12 @@
 // Integration tests for tinct crate
 use tempfile::TempDir;
-use tinct::{color, theme};
+use tinct::theme_file::{Theme, ThemeMode};
+use tinct::{base16, color, pywal, theme, Scheme};
 
 #[test]
 fn test_color_functions() {
+    use tinct::{ContrastLevel, ContrastMetric};
+
     // Test hex to RGB conversion
     let rgb = color::hex_to_rgb("#ffffff").unwrap();
     assert_eq!(rgb.r, 255);
@@ -73,19 +76,67 @@ fn test_color_functions() {
     let ratio = color::get_contrast_ratio("#ffffff", "#ffffff").unwrap();
     assert!((ratio - 1.0).abs() < 0.1); // Same colors should have ratio of 1
 
+    // Any CSS syntax is accepted; translucent colors are blended over the
+    // background, which must be opaque
+    let hex = color::get_contrast_ratio("#6750a4", "#ffffff").unwrap();
+    assert_eq!(
+        color::get_contrast_ratio("rgb(103 80 164)", "#fff").unwrap(),
+        hex
+    );
+    assert_eq!(
+        color::get_contrast_ratio("hsl(0, 0%, 100%)", "black").unwrap(),
+        color::get_contrast_ratio("#ffffff", "#000000").unwrap()
+    );
+    assert_eq!(
+        color::get_contrast_ratio("#ffffff80", "#000").unwrap(),
+        color::get_contrast_ratio("#808080", "#000000").unwrap()
+    );
+    assert!(color::get_contrast_ratio("#ffffff", "#00000080").is_err());
+    assert!(color::get_luminance("rgba(0, 0, 0, 0.5)").is_err());
+    assert_eq!(color::get_luminance("#fff").unwrap(), lum_white);
+
     // Test if color is light
     assert_eq!(color::is_light_color("#ffffff").unwrap(), true);
     assert_eq!(color::is_light_color("#000000").unwrap(), false);
     assert_eq!(color::is_light_color("#888888").unwrap(), false); // Mid gray is considered dark
 
     // Test generating on color
-    // Test with light background - should return dark text color
-    let color_result = color::generate_on_color("#ffffff", true).unwrap();
-    assert!(color_result == "#000000" || color_result == "#1c1b1f"); // Either pure black or dark gray
+    // Test with light background - should return dark text color with 7:1 contrast
+    let color_result =
+        color::generate_on_color("#ffffff", ContrastLevel::STANDARD, ContrastMetric::Wcag).unwrap();
+    assert!(!color::is_light_color(&color_result).unwrap());
+    assert!(color::get_contrast_ratio(&color_result, "#ffffff").unwrap() >= 6.95);
 
     // Test with dark background - should return light text color
-    let color_result = color::generate_on_color("#000000", true).unwrap();
-    assert!(color_result == "#ffffff" || color_result == "#e6e1e5"); // Either pure white or light gray
+    let color_result =
+        color::generate_on_color("#000000", ContrastLevel::STANDARD, ContrastMetric::Wcag).unwrap();
+    let text = color::hex_to_rgb(&color_result).unwrap();
+    assert!(color::rgb_to_hct(text.r, text.g, text.b).t > 50.0);
+    assert!(color::get_contrast_ratio(&color_result, "#000000").unwrap() >= 6.95);
+
+    // Test with a colored background - the text color is tinted with its hue
+    let color_result =
+        color::generate_on_color("#d0bcff", ContrastLevel::STANDARD, ContrastMetric::Wcag).unwrap();
+    let text = color::hex_to_rgb(&color_result).unwrap();
+    let text = color::rgb_to_hct(text.r, text.g, text.b);
+    let background = color::rgb_to_hct(0xd0, 0xbc, 0xff);
+    assert!(text.c > 10.0 && color::difference_degrees(text.h, background.h) < 5.0);
+    assert!(color::get_contrast_ratio(&color_result, "#d0bcff").unwrap() >= 6.95);
+
+    // The contrast level and metric set the target
+    let reduced = color::generate_on_color(
+        "#d0bcff",
+        ContrastLevel::new(-1.0).unwrap(),
+        ContrastMetric::Wcag,
+    )
+    .unwrap();
+    let reduced_ratio = color::get_contrast_ratio(&reduced, "#d0bcff").unwrap();
+    assert!((4.4..6.95).contains(&reduced_ratio));
+    let apca =
+        color::generate_on_color("#6750a4", ContrastLevel::STANDARD, ContrastMetric::Apca).unwrap();
+    let text = color::hex_to_rgb(&apca).unwrap();
+    let background = color::hex_to_rgb("#6750a4").unwrap();
+    assert!(color::apca_contrast(&text, &background).abs() >= 74.0);
 
     // Test adjusting lightness and saturation
     let result = color::adjust_lightness_and_saturation("#ff8080", -10.0, 10.0).unwrap();
@@ -95,17 +146,272 @@ fn test_color_functions() {
     assert_eq!(color::clamp(5.0, 0.0, 10.0), 5.0);
     assert_eq!(color::clamp(-1.0, 0.0, 10.0), 0.0);
     assert_eq!(color::clamp(15.0, 0.0, 10.0), 10.0);
-    
+
     // Test HCT functionality
     let hct = color::rgb_to_hct(255, 0, 0); // Red
-    assert_eq!(hct.h as u32, 0); // Hue should be around 0 for red
-    
-    let hct_color = color::Hct::from_hct(0.0, 100.0, 50.0);
+    assert!((hct.h - 27.408).abs() < 0.01); // CAM16 hue of sRGB red
+    assert!((hct.c - 113.357).abs() < 0.01);
+    assert!((hct.t - 53.233).abs() < 0.01);
+
+    let hct_color = color::Hct::from_hct(27.408, 113.357, 53.233);
     let rgb_from_hct = hct_color.to_rgb();
-    // The RGB values should be close to red (255, 0, 0) when converted back from HCT
-    assert!(rgb_from_hct.r > 200); // Red component should be high
-    assert!(rgb_from_hct.g < 50); // Green component should be low
-    assert!(rgb_from_hct.b < 50); // Blue component should be low
+    // Converting the HCT of red back should give red again
+    assert!(rgb_from_hct.r > 250); // Red component should be high
+    assert!(rgb_from_hct.g < 5); // Green component should be low
+    assert!(rgb_from_hct.b < 5); // Blue component should be low
+}
+
+#[test]
+fn test_hct_tone_and_gamut() {
+    // The tone of a solved color must match the requested tone, even when
+    // the requested chroma is out of gamut and has to be reduced
+    for hue in [0.0, 45.0, 120.0, 209.0, 282.0, 330.0] {
+        for tone in [6.0, 30.0, 50.0, 80.0, 98.0] {
+            let rgb = color::Hct::from_hct(hue, 200.0, tone).to_rgb();
+            let back = color::rgb_to_hct(rgb.r, rgb.g, rgb.b);
+            assert!(
+                (back.t - tone).abs() < 0.5,
+                "tone {} came back as {}",
+                tone,
+                back.t
+            );
+        }
+    }
+
+    // Round trip of an in-gamut color
+    let rgb = color::hex_to_rgb("#6750a4").unwrap();
+    let hct = color::rgb_to_hct(rgb.r, rgb.g, rgb.b);
+    assert_eq!(hct.to_hex(), "#6750a4");
+
+    // Zero chroma produces a neutral gray with the requested L*
+    let gray = color::Hct::from_hct(120.0, 0.0, 50.0).to_rgb();
+    assert_eq!((gray.r, gray.g, gray.b), (119, 119, 119));
+}
+
+#[test]
+fn test_oklab_oklch() {
+    let red = color::hex_to_rgb("#ff0000").unwrap();
+    let lab = color::Oklab::from_rgb(&red);
+    assert!((lab.l - 0.62796).abs() < 1e-4);
+    assert!((lab.a - 0.22486).abs() < 1e-4);
+    assert!((lab.b - 0.12585).abs() < 1e-4);
+    assert_eq!(lab.to_rgb(), red);
+
+    let lch = color::Oklch::from_rgb(&red);
+    assert!((lch.c - 0.25768).abs() < 1e-4);
+    assert!((lch.h - 29.234).abs() < 1e-2);
+    assert_eq!(lch.to_css(), "oklch(62.80% 0.2577 29.23)");
+    assert_eq!(lab.to_css(), "oklab(62.80% 0.2249 0.1258)");
+
+    let white = color::Oklch::from_rgb(&color::Rgb {
+        r: 255,
+        g: 255,
+        b: 255,
+    });
+    assert!((white.l - 1.0).abs() < 1e-4);
+    assert!(white.c < 1e-4);
+
+    // Out of gamut chroma is reduced without changing the hue
+    let vivid = color::Oklch {
+        l: 0.7,
+        c: 0.4,
+        h: 150.0,
+    };
+    assert!(!vivid.in_srgb_gamut());
+    let mapped = color::Oklch::from_rgb(&vivid.to_rgb());
+    assert!((mapped.h - 150.0).abs() < 2.0);
+    assert!((mapped.l - 0.7).abs() < 0.01);
+    let mapped = color::Oklch::from_rgb(&vivid.to_oklab().to_rgb());
+    assert!((mapped.h - 150.0).abs() < 2.0);
+
+    // OKLCH adjustments keep the hue
+    let lighter = color::adjust_oklch_lightness("#6750a4", 10.0).unwrap();
+    let before = color::Oklch::from_rgb(&color::hex_to_rgb("#6750a4").unwrap());
+    let after = color::Oklch::from_rgb(&color::hex_to_rgb(&lighter).unwrap());
+    assert!((after.l - before.l - 0.1).abs() < 0.01);
+    assert!((after.h - before.h).abs() < 2.0);
+    let muted = color::adjust_oklch_chroma("#6750a4", -1.0).unwrap();
+    let muted = color::Oklch::from_rgb(&color::hex_to_rgb(&muted).unwrap());
+    assert!(muted.c < 0.01);
+
+    // Template properties
+    let palette = theme::palette_from_seed("#6750a4", true, &Default::default()).unwrap();
+    let output = theme::process_template(
+        "{{colors.primary.default.oklch}} {{colors.primary.default.oklab}}",
+        &palette,
+        "dark",
+    );
+    assert!(output.starts_with("oklch("));
+    assert!(output.contains(" oklab("));
+}
+
+#[test]
+fn test_display_p3_and_gamut_mapping() {
+    let red = color::Rgb { r: 255, g: 0, b: 0 };
+    let p3 = color::DisplayP3::from_rgb(&red);
+    assert!((p3.r - 0.9175).abs() < 1e-3);
+    assert!((p3.g - 0.2003).abs() < 1e-3);
+    assert!((p3.b - 0.1386).abs() < 1e-3);
+    assert!(p3.to_css().starts_with("color(display-p3 0.917"));
+    assert!(p3.in_gamut());
+
+    // Chroma beyond sRGB survives in Display P3, at the same tone
+    let vivid = color::Hct::from_hct(140.0, 150.0, 70.0);
+    let p3 = vivid.to_display_p3();
+    assert!(p3.in_gamut());
+    let [x, y, z] = p3.to_xyz();
+    let p3_lch = color::Lab::from_xyz(x, y, z).to_lch();
+    let srgb_lch = color::Lch::from_rgb(&vivid.to_rgb());
+    assert!((p3_lch.l - 70.0).abs() < 0.1);
+    assert!(p3_lch.c > srgb_lch.c + 10.0);
+    assert!((p3_lch.h - srgb_lch.h).abs() < 5.0);
+    let white = color::Hct::from_hct(140.0, 150.0, 100.0).to_display_p3();
+    assert_eq!(
+        white,
+        color::DisplayP3::from_rgb(&color::Rgb {
+            r: 255,
+            g: 255,
+            b: 255
+        })
+    );
+
+    // Colors outside a gamut are brought into it, those inside are untouched
+    let oklch = color::Oklch {
+        l: 0.6,
+        c: 0.25,
+        h: 145.0,
+    };
+    assert!(!color::Gamut::Srgb.contains(&oklch));
+    assert!(color::Gamut::DisplayP3.contains(&oklch));
+    let p3 = color::Gamut::DisplayP3.map(&oklch);
+    let back = color::Gamut::DisplayP3.to_oklch(p3);
+    assert!((back.c - oklch.c).abs() < 1e-6);
+    let srgb = color::Gamut::Srgb.map(&oklch);
+    assert!(srgb.iter().all(|c| (0.0..=1.0).contains(c)));
+    let mapped = color::Gamut::Srgb.to_oklch(srgb);
+    assert!(mapped.c < oklch.c);
+    assert!((mapped.h - oklch.h).abs() < 4.0);
+    let reduced = color::Gamut::Srgb.to_oklch(color::Gamut::Srgb.reduce_chroma(&oklch));
+    assert!(reduced.c < mapped.c);
+    assert!((reduced.h - oklch.h).abs() < 0.1);
+    assert!((reduced.l - oklch.l).abs() < 1e-3);
+
+    // Template property
+    let options = theme::PaletteOptions {
+        scheme: Some(Scheme::Vibrant),
+        ..Default::default()
+    };
+    let palette = theme::palette_from_seed("#00ff00", false, &options).unwrap();
+    let output = theme::process_template(
+        "{{colors.primary.default.p3}} {{colors.palettes.primary.tone_50.p3}}",
+        &palette,
+        "light",
+    );
+    assert!(output.starts_with("color(display-p3 "));
+    assert_ne!(
+        palette.primary.default.p3,
+        color::DisplayP3::from_rgb(&color::hex_to_rgb(&palette.primary.default.hex).unwrap())
+            .to_css()
+    );
+}
+
+#[test]
+fn test_lab_and_delta_e() {
+    let red = color::hex_to_rgb("#ff0000").unwrap();
+    let lab = color::Lab::from_rgb(&red);
+    assert!((lab.l - 53.2329).abs() < 1e-3);
+    assert!((lab.a - 80.0906).abs() < 1e-2);
+    assert!((lab.b - 67.2008).abs() < 1e-2);
+    assert_eq!(lab.to_rgb(), red);
+    let lch = lab.to_lch();
+    assert!((lch.c - 104.5488).abs() < 1e-2);
+    assert!((lch.h - 39.999).abs() < 1e-2);
+    assert_eq!(lch.to_rgb(), red);
+
+    // Reference pairs from Sharma, Wu and Dalal (2005)
+    let pairs = [
+        ((50.0, 2.6772, -79.7751), (50.0, 0.0, -82.7485), 2.0425),
+        ((50.0, 0.0, 0.0), (50.0, -1.0, 2.0), 2.3669),
+        ((50.0, 2.5, 0.0), (73.0, 25.0, -18.0), 27.1492),
+        ((50.0, 2.5, 0.0), (50.0, 0.0, -2.5), 4.3065),
+        (
+            (60.2574, -34.0099, 36.2677),
+            (60.4626, -34.1751, 39.4387),
+            1.2644,
+        ),
+    ];
+    for ((l1, a1, b1), (l2, a2, b2), expected) in pairs {
+        let x = color::Lab {
+            l: l1,
+            a: a1,
+            b: b1,
+        };
+        let y = color::Lab {
+            l: l2,
+            a: a2,
+            b: b2,
+        };
+        assert!((color::delta_e_2000(&x, &y) - expected).abs() < 1e-4);
+        assert!((color::delta_e_2000(&y, &x) - expected).abs() < 1e-4);
+    }
+
+    assert_eq!(
+        color::get_color_difference("#6750a4", "#6750a4").unwrap(),
+        0.0
+    );
+    assert!(color::get_color_difference("#000000", "#ffffff").unwrap() > 99.0);
+}
+
+#[test]
+fn test_parse_color() {
+    let parse = |s: &str| color::parse_color(s).unwrap();
+    let rgba = |r, g, b, a| color::Rgba { r, g, b, a };
+
+    assert_eq!(parse("#1e1e2e"), rgba(0x1e, 0x1e, 0x2e, 255));
+    assert_eq!(parse("1E1E2E"), rgba(0x1e, 0x1e, 0x2e, 255));
+    assert_eq!(parse("#1e1e2ecc"), rgba(0x1e, 0x1e, 0x2e, 0xcc));
+    assert_eq!(parse("#f0a"), rgba(0xff, 0x00, 0xaa, 255));
+    assert_eq!(parse("#f0a8"), rgba(0xff, 0x00, 0xaa, 0x88));
+    assert_eq!(parse("rgb(30, 30, 46)"), rgba(30, 30, 46, 255));
+    assert_eq!(parse("rgba(30, 30, 46, 0.8)"), rgba(30, 30, 46, 204));
+    assert_eq!(parse("rgb(30 30 46 / 80%)"), rgba(30, 30, 46, 204));
+    assert_eq!(parse("rgb(100% 0% 50%)"), rgba(255, 0, 128, 255));
+    assert_eq!(parse("hsl(0, 100%, 50%)"), rgba(255, 0, 0, 255));
+    assert_eq!(parse("hsla(120deg 100% 25% / 0.5)"), rgba(0, 128, 0, 128));
+    assert_eq!(parse("RebeccaPurple"), rgba(0x66, 0x33, 0x99, 255));
+    assert_eq!(parse("transparent").a, 0);
+
+    for invalid in [
+        "",
+        "#12345",
+        "#ggg",
+        "rgb(1, 2)",
+        "hsl(a, b, c)",
+        "notacolor",
+    ] {
+        assert!(color::parse_color(invalid).is_err(), "{}", invalid);
+    }
+
+    // Alpha from the theme reaches the template properties
+    let theme_data = serde_json::json!({
+        "primary": "rgb(103 80 164 / 50%)",
+        "surface": "#1e1e2ecc",
+    });
+    let palette = theme::generate_palette(&theme_data, true, false).unwrap();
+    let output = theme::process_template(
+        "{{colors.surface.default.hex}} {{colors.surface.default.rgba}} {{colors.surface.default.hsla}} {{colors.surface.default.alpha}}",
+        &palette,
+        "dark",
+    );
+    assert_eq!(
+        output,
+        "#1e1e2e rgba(30, 30, 46, 0.8) hsla(240, 21%, 14%, 0.8) 204"
+    );
+    assert_eq!(palette.primary.default.alpha, 128);
+    assert_eq!(palette.primary.default.rgba, "rgba(103, 80, 164, 0.5)");
+    assert_eq!(palette.on_primary.default.alpha, 255);
+    assert!(palette.on_primary.default.hsla.ends_with(", 1.0)"));
+    assert!(palette.on_primary.default.rgba.ends_with(", 1.0)"));
 }
 
 #[test]
@@ -147,7 +453,7 @@ fn test_theme_functions() {
     assert!(!palette.primary.default.hex.is_empty());
     assert!(!palette.primary.default.rgb.is_empty());
     assert!(!palette.primary.default.hsl.is_empty());
-    
+
     // Test template processing
     let template_content = "Primary color: {{colors.primary.default.hex}}, Mode: {{mode}}";
     let result = theme::process_template(template_content, &palette, "dark");
@@ -157,6 +463,1436 @@ fn test_theme_functions() {
     assert!(result.contains(", Mode: "));
 }
 
+#[test]
+fn test_tonal_palettes() {
+    let palette = color::TonalPalette::from_rgb(&color::hex_to_rgb("#6750a4").unwrap());
+    assert_eq!(palette.hex(0.0), "#000000");
+    assert_eq!(palette.hex(100.0), "#ffffff");
+    for tone in [10.0, 35.0, 40.0, 90.0] {
+        let rgb = palette.tone(tone);
+        let hct = color::rgb_to_hct(rgb.r, rgb.g, rgb.b);
+        assert!((hct.t - tone).abs() < 0.5);
+    }
+
+    let theme = serde_json::json!({ "primary": "#6750a4" });
+    let palette = theme::generate_palette(&theme, true, false).unwrap();
+    let template =
+        "{{colors.palettes.primary.tone_35.hex}} {{ colors.palettes.neutral.tone_100.hex }} \
+                    {{colors.palettes.error.tone_0.rgb}} {{colors.palettes.nope.tone_40.hex}} \
+                    {{colors.palettes.primary.tone_101.hex}}";
+    let result = theme::process_template(template, &palette, "dark");
+    let expected = format!(
+        "{} #ffffff rgb(0, 0, 0) #000000 #000000",
+        palette.palettes.primary.hex(35.0)
+    );
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn test_scheme_variants() {
+    let theme = ThemeMode::from_value(&serde_json::json!({ "primary": "#6750a4" })).unwrap();
+    let source = color::rgb_to_hct(0x67, 0x50, 0xa4);
+    let options = |scheme| theme::PaletteOptions {
+        scheme: Some(scheme),
+        ..Default::default()
+    };
+
+    // Tonal spot: primary is tone 80 (dark) / 40 (light) of the source hue at chroma 36
+    let dark =
+        theme::generate_palette_with_options(&theme, true, false, &options(Scheme::TonalSpot))
+            .unwrap();
+    let expected = color::Hct::from_hct(source.h, 36.0, 80.0).to_hex();
+    assert_eq!(dark.primary.default.hex, expected);
+    let light =
+        theme::generate_palette_with_options(&theme, false, false, &options(Scheme::TonalSpot))
+            .unwrap();
+    let expected = color::Hct::from_hct(source.h, 36.0, 40.0).to_hex();
+    assert_eq!(light.primary.default.hex, expected);
+    assert!((dark.palettes.tertiary.hue - color::sanitize_degrees(source.h + 60.0)).abs() < 1e-9);
+
+    // Vibrant is more colorful than tonal spot, monochrome has no chroma at all
+    let vibrant =
+        theme::generate_palette_with_options(&theme, true, false, &options(Scheme::Vibrant))
+            .unwrap();
+    assert!(vibrant.palettes.primary.chroma > dark.palettes.primary.chroma);
+    let mono =
+        theme::generate_palette_with_options(&theme, true, false, &options(Scheme::Monochrome))
+            .unwrap();
+    for role in [&mono.primary, &mono.tertiary, &mono.surface_container] {
+        assert_eq!(role.default.red, role.default.green);
+        assert_eq!(role.default.green, role.default.blue);
+    }
+
+    // Fruit salad rotates the primary hue away from the source
+    let fruit =
+        theme::generate_palette_with_options(&theme, true, false, &options(Scheme::FruitSalad))
+            .unwrap();
+    assert!((fruit.palettes.primary.hue - color::sanitize_degrees(source.h - 50.0)).abs() < 1e-9);
+
+    // Scheme can be set from the config file
+    let config: tinct::Config = toml::from_str(
+        r#"
+        scheme = "fruit-salad"
+
+        [terminal.kitty]
+        input_path = "kitty.conf"
+        output_path = "out/kitty.conf"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.scheme, Some(Scheme::FruitSalad));
+    assert!(config.groups["terminal"].contains_key("kitty"));
+}
+
+#[test]
+fn test_contrast_levels() {
+    use tinct::ContrastLevel;
+
+    assert_eq!(
+        "standard".parse::<ContrastLevel>(),
+        Ok(ContrastLevel::STANDARD)
+    );
+    assert_eq!("HIGH".parse::<ContrastLevel>(), Ok(ContrastLevel::HIGH));
+    assert_eq!("-0.5".parse::<ContrastLevel>().unwrap().value(), -0.5);
+    assert!("1.5".parse::<ContrastLevel>().is_err());
+    assert!("loud".parse::<ContrastLevel>().is_err());
+
+    let theme = serde_json::json!({ "primary": "#6750a4" });
+    let theme_mode = ThemeMode::from_value(&theme).unwrap();
+    let generate = |is_dark, contrast| {
+        let options = theme::PaletteOptions {
+            scheme: Some(Scheme::TonalSpot),
+            contrast,
+            ..Default::default()
+        };
+        theme::generate_palette_with_options(&theme_mode, is_dark, false, &options).unwrap()
+    };
+    let ratio = |a: &theme::ColorEntry, b: &theme::ColorEntry| {
+        color::get_contrast_ratio(&a.default.hex, &b.default.hex).unwrap()
+    };
+
+    for is_dark in [true, false] {
+        let standard = generate(is_dark, ContrastLevel::STANDARD);
+        let high = generate(is_dark, ContrastLevel::HIGH);
+        let reduced = generate(is_dark, ContrastLevel::new(-1.0).unwrap());
+
+        // Higher contrast moves roles further from their backgrounds
+        assert!(
+            ratio(&high.primary_container, &high.surface_bright)
+                > ratio(&standard.primary_container, &standard.surface_bright)
+                || ratio(&high.primary_container, &high.surface_dim)
+                    > ratio(&standard.primary_container, &standard.surface_dim)
+        );
+        assert!(
+            ratio(&high.on_surface, &high.surface)
+                >= ratio(&standard.on_surface, &standard.surface)
+        );
+        assert!(ratio(&high.outline, &high.surface) > ratio(&standard.outline, &standard.surface));
+        assert!(ratio(&high.on_primary_container, &high.primary_container) >= 4.5);
+
+        // Reduced contrast brings them closer
+        assert!(
+            ratio(&reduced.outline, &reduced.surface) < ratio(&standard.outline, &standard.surface)
+        );
+
+        // Fixed roles keep their light tones at reduced contrast, apart from the containers
+        let tone_of = |entry: &theme::ColorEntry| {
+            let rgb = entry.default.rgb();
+            color::rgb_to_hct(rgb.r, rgb.g, rgb.b).t
+        };
+        for level in [-0.5, -1.0] {
+            let reduced = generate(is_dark, ContrastLevel::new(level).unwrap());
+            assert!((tone_of(&reduced.primary_fixed) - 90.0).abs() < 1.0);
+            assert!((tone_of(&reduced.primary_fixed_dim) - 80.0).abs() < 1.0);
+            assert_ne!(
+                reduced.primary_fixed.default.hex,
+                reduced.primary_container.default.hex
+            );
+            assert!(ratio(&reduced.on_primary_fixed, &reduced.primary_fixed_dim) >= 4.4);
+        }
+
+        // Raised contrast keeps containers at tones their on-colors can reach
+        let medium = generate(is_dark, ContrastLevel::MEDIUM);
+        assert!(ratio(&medium.on_primary_container, &medium.primary_container) >= 10.5);
+        assert_ne!(medium.on_primary_container.default.hex, "#000000");
+        assert_eq!(
+            medium.surface_container_highest.default.hex,
+            standard.surface_container_highest.default.hex
+        );
+    }
+
+    // In light mode, raised contrast moves the fixed roles to their dark pair
+    let medium = generate(false, ContrastLevel::MEDIUM);
+    assert_eq!(
+        medium.primary_fixed.default.hex,
+        medium.palettes.primary.hex(40.0)
+    );
+    assert_eq!(
+        medium.primary_fixed_dim.default.hex,
+        medium.palettes.primary.hex(30.0)
+    );
+
+    // The standard level leaves a hand-written theme's tones unchanged
+    let standard = theme::generate_palette(&theme, true, false).unwrap();
+    let options = theme::PaletteOptions {
+        contrast: ContrastLevel::STANDARD,
+        ..Default::default()
+    };
+    let explicit =
+        theme::generate_palette_with_options(&theme_mode, true, false, &options).unwrap();
+    assert_eq!(
+        standard.primary_container.default.hex,
+        explicit.primary_container.default.hex
+    );
+
+    let config: tinct::Config = toml::from_str("contrast = \"medium\"").unwrap();
+    assert_eq!(config.contrast, Some(ContrastLevel::MEDIUM));
+    let config: tinct::Config = toml::from_str("contrast = -1").unwrap();
+    assert_eq!(config.contrast.unwrap().value(), -1.0);
+}
+
+#[test]
+fn test_solved_on_colors() {
+    let theme_data = serde_json::json!({
+        "primary": "#6750a4",
+        "secondary": "#625b71",
+        "tertiary": "#7d5260",
+        "surface": "#1c1b1f",
+    });
+    let hct = |color_format: &theme::ColorFormat| {
+        let rgb = color_format.rgb();
+        color::rgb_to_hct(rgb.r, rgb.g, rgb.b)
+    };
+
+    for is_dark_mode in [true, false] {
+        let palette = theme::generate_palette(&theme_data, is_dark_mode, false).unwrap();
+        let pairs = [
+            (&palette.primary, &palette.on_primary),
+            (&palette.primary_container, &palette.on_primary_container),
+            (&palette.secondary, &palette.on_secondary),
+            (
+                &palette.secondary_container,
+                &palette.on_secondary_container,
+            ),
+            (&palette.tertiary, &palette.on_tertiary),
+            (&palette.tertiary_container, &palette.on_tertiary_container),
+            (&palette.error, &palette.on_error),
+            (&palette.error_container, &palette.on_error_container),
+            (&palette.surface, &palette.on_surface),
+            (&palette.primary_fixed_dim, &palette.on_primary_fixed),
+            (&palette.inverse_surface, &palette.inverse_on_surface),
+        ];
+        for (background, on_color) in pairs {
+            let background = &background.default.hex;
+            let ratio = color::get_contrast_ratio(&on_color.default.hex, background).unwrap();
+            // Mid-tone backgrounds cannot reach 7:1 with any color; then the best is used
+            let best = color::get_contrast_ratio("#ffffff", background)
+                .unwrap()
+                .max(color::get_contrast_ratio("#000000", background).unwrap());
+            assert!(
+                ratio >= best.min(7.0) - 0.1,
+                "{} on {}: {:.2}",
+                on_color.default.hex,
+                background,
+                ratio
+            );
+        }
+
+        // On-colors share the hue of their background instead of being black or white
+        let container = hct(&palette.primary_container.default);
+        let on_container = hct(&palette.on_primary_container.default);
+        assert!(on_container.c > 5.0);
+        assert!(color::difference_degrees(on_container.h, container.h) < 10.0);
+        assert_ne!(palette.on_primary_container.default.hex, "#ffffff");
+        assert_ne!(palette.on_primary_container.default.hex, "#000000");
+
+        // The inverse primary is the primary hue at the other mode's tone
+        let inverse_primary = hct(&palette.inverse_primary.default);
+        assert!(
+            color::difference_degrees(inverse_primary.h, hct(&palette.primary.default).h) < 5.0
+        );
+    }
+
+    // Colors set by the theme are kept
+    let theme_data = serde_json::json!({ "primary": "#6750a4", "on_primary": "#ffffff" });
+    let palette = theme::generate_palette(&theme_data, true, false).unwrap();
+    assert_eq!(palette.on_primary.default.hex, "#ffffff");
+}
+
+#[test]
+fn test_image_source_color() {
+    // Mostly a saturated blue, with some gray and a little orange
+    let blue = color::Rgb {
+        r: 30,
+        g: 90,
+        b: 200,
+    };
+    let mut pixels = vec![blue; 700];
+    pixels.extend(vec![
+        color::Rgb {
+            r: 128,
+            g: 128,
+            b: 128
+        };
+        250
+    ]);
+    pixels.extend(vec![
+        color::Rgb {
+            r: 230,
+            g: 140,
+            b: 40
+        };
+        50
+    ]);
+
+    let quantized = tinct::quantize::quantize(&pixels, 128);
+    assert_eq!(quantized.values().sum::<u32>(), 1000);
+    assert_eq!(quantized.get(&blue), Some(&700));
+
+    let source = tinct::wallpaper::source_color_from_pixels(&pixels);
+    assert_eq!(source, blue);
+
+    // Grayscale images have no usable color and fall back to Google Blue
+    let gray = vec![
+        color::Rgb {
+            r: 100,
+            g: 100,
+            b: 100
+        };
+        100
+    ];
+    let source = tinct::wallpaper::source_color_from_pixels(&gray);
+    assert_eq!(source, tinct::score::FALLBACK_COLOR);
+
+    // The same from an image file, with transparent pixels ignored
+    let temp_dir = TempDir::new().expect("create temp dir");
+    let image_path = temp_dir.path().join("wallpaper.png");
+    let image = image::RgbaImage::from_fn(64, 64, |x, _| {
+        if x < 48 {
+            image::Rgba([30, 90, 200, 255])
+        } else {
+            image::Rgba([230, 140, 40, 0])
+        }
+    });
+    image.save(&image_path).unwrap();
+    let source_hex = tinct::wallpaper::source_hex_from_image(image_path.to_str().unwrap()).unwrap();
+    let source_hct = color::rgb_to_hct(30, 90, 200);
+    let extracted = color::hex_to_rgb(&source_hex).unwrap();
+    let extracted_hct = color::rgb_to_hct(extracted.r, extracted.g, extracted.b);
+    assert!((extracted_hct.h - source_hct.h).abs() < 5.0);
+
+    // Seed themes are turned into full palettes by a dynamic scheme
+    let seed = theme::seed_theme(&source_hex);
+    let (dark, _) = Theme::from_value(&seed).unwrap().mode("dark").unwrap();
+    let options = theme::PaletteOptions {
+        scheme: Some(Scheme::TonalSpot),
+        ..Default::default()
+    };
+    let palette = theme::generate_palette_with_options(&dark, true, false, &options).unwrap();
+    assert!(palette.surface.default.hex.starts_with('#'));
+
+    assert!(tinct::wallpaper::source_hex_from_image("/nonexistent/wallpaper.png").is_err());
+}
+
+#[test]
+fn test_palette_from_seed() {
+    let options = theme::PaletteOptions::default();
+    let dark = theme::palette_from_seed("#6750a4", true, &options).unwrap();
+    let light = theme::palette_from_seed("#6750a4", false, &options).unwrap();
+
+    // Tonal spot is used by default: primary is tone 80 in dark mode, 40 in light mode
+    let primary = color::hex_to_rgb(&dark.primary.default.hex).unwrap();
+    assert!((color::rgb_to_hct(primary.r, primary.g, primary.b).t - 80.0).abs() < 1.0);
+    let primary = color::hex_to_rgb(&light.primary.default.hex).unwrap();
+    assert!((color::rgb_to_hct(primary.r, primary.g, primary.b).t - 40.0).abs() < 1.0);
+    assert_eq!(
+        dark.palettes.primary.hex(40.0),
+        light.palettes.primary.hex(40.0)
+    );
+
+    // Other schemes can still be chosen
+    let monochrome = theme::palette_from_seed(
+        "#6750a4",
+        true,
+        &theme::PaletteOptions {
+            scheme: Some(Scheme::Monochrome),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(monochrome.palettes.primary.chroma, 0.0);
+
+    assert!(theme::palette_from_seed("not a color", true, &options).is_err());
+}
+
+#[test]
+fn test_apca_contrast() {
+    // Reference values from the APCA 0.0.98G-4g implementation
+    let gray = color::Rgb {
+        r: 136,
+        g: 136,
+        b: 136,
+    };
+    let white = color::Rgb {
+        r: 255,
+        g: 255,
+        b: 255,
+    };
+    let black = color::Rgb { r: 0, g: 0, b: 0 };
+    let light_gray = color::Rgb {
+        r: 170,
+        g: 170,
+        b: 170,
+    };
+    assert!((color::apca_contrast(&gray, &white) - 63.056).abs() < 1e-2);
+    assert!((color::apca_contrast(&white, &gray) + 68.541).abs() < 1e-2);
+    assert!((color::apca_contrast(&black, &light_gray) - 58.146).abs() < 1e-2);
+    assert!((color::apca_contrast(&light_gray, &black) + 56.241).abs() < 1e-2);
+    assert_eq!(color::get_apca_contrast("#777777", "#777777").unwrap(), 0.0);
+
+    let metric = tinct::ContrastMetric::Apca;
+    assert_eq!(metric.target(4.5), 60.0);
+    assert_eq!(metric.target(21.0), 105.0);
+    assert_eq!(tinct::ContrastMetric::Wcag.target(4.5), 4.5);
+
+    // On-colors are solved against the chosen metric (Lc 75 at standard contrast);
+    // tones are solved as grays, so colorful pairs land within a few Lc of it
+    for is_dark in [true, false] {
+        let options = theme::PaletteOptions {
+            metric,
+            ..Default::default()
+        };
+        let palette = theme::palette_from_seed("#6750a4", is_dark, &options).unwrap();
+        let lc = color::get_apca_contrast(
+            &palette.on_primary.default.hex,
+            &palette.primary.default.hex,
+        )
+        .unwrap();
+        assert!(lc.abs() >= 70.0, "on_primary Lc {}", lc);
+        let lc = color::get_apca_contrast(
+            &palette.on_surface.default.hex,
+            &palette.surface.default.hex,
+        )
+        .unwrap();
+        assert!(lc.abs() >= 70.0, "on_surface Lc {}", lc);
+    }
+
+    // Generated on-colors pass the check; a gray that only passes WCAG is
+    // flagged under APCA
+    let palette = theme::palette_from_seed("#6750a4", true, &Default::default()).unwrap();
+    assert!(tinct::check::low_contrast_pairs(&palette, metric).is_empty());
+    let theme = ThemeMode::from_value(&serde_json::json!({
+        "primary": "#6750a4",
+        "surface": "#000000",
+        "on_surface": "#999999",
+    }))
+    .unwrap();
+    let palette =
+        theme::generate_palette_with_options(&theme, true, false, &Default::default()).unwrap();
+    let wcag = tinct::check::low_contrast_pairs(&palette, tinct::ContrastMetric::Wcag);
+    assert!(wcag.iter().all(|pair| pair.foreground != "on_surface"));
+    let apca = tinct::check::low_contrast_pairs(&palette, metric);
+    let pair = apca
+        .iter()
+        .find(|pair| pair.foreground == "on_surface")
+        .unwrap();
+    assert_eq!(pair.background, "surface");
+    assert_eq!(pair.target, 60.0);
+    assert!(pair.contrast < 60.0);
+
+    let config: tinct::Config = toml::from_str("contrast_metric = \"apca\"").unwrap();
+    assert_eq!(config.contrast_metric, Some(tinct::ContrastMetric::Apca));
+}
+
+#[test]
+fn test_color_blending() {
+    let rgb = |r, g, b| color::Rgb { r, g, b };
+    let (red, blue) = (rgb(255, 0, 0), rgb(0, 0, 255));
+    let (black, white) = (rgb(0, 0, 0), rgb(255, 255, 255));
+    let hct = |c: &color::Rgb| color::rgb_to_hct(c.r, c.g, c.b);
+
+    // Every blend returns its endpoints at 0.0 and 1.0
+    type Blend = fn(&color::Rgb, &color::Rgb, f64) -> color::Rgb;
+    let blends: [Blend; 3] = [
+        color::mix_linear_srgb,
+        color::mix_oklab,
+        color::blend_cam16_ucs,
+    ];
+    for blend in blends {
+        let start = blend(&red, &blue, 0.0);
+        let end = blend(&red, &blue, 1.0);
+        assert!(
+            color::delta_e_2000(&color::Lab::from_rgb(&start), &color::Lab::from_rgb(&red)) < 1.0
+        );
+        assert!(
+            color::delta_e_2000(&color::Lab::from_rgb(&end), &color::Lab::from_rgb(&blue)) < 1.0
+        );
+    }
+
+    // Linear light mixes to the physical average; OKLab to a perceptual middle gray
+    assert_eq!(
+        color::mix_linear_srgb(&black, &white, 0.5),
+        rgb(188, 188, 188)
+    );
+    let gray = color::mix_oklab(&black, &white, 0.5);
+    assert!(gray.r == gray.g && gray.g == gray.b);
+    assert!((color::Oklab::from_rgb(&gray).l - 0.5).abs() < 0.01);
+    assert_eq!(color::mix_oklab(&red, &blue, 2.0), blue);
+
+    // The CAM16-UCS blend keeps some chroma halfway between complementary hues
+    let middle = color::blend_cam16_ucs(&red, &blue, 0.5);
+    assert!(hct(&middle).c > 10.0);
+
+    // The hue-only blend keeps chroma and tone of the first color
+    let red_hct = hct(&red);
+    let hue_blended = hct(&color::blend_hct_hue(&red, &blue, 0.5));
+    assert!((hue_blended.t - red_hct.t).abs() < 1.0);
+    assert!(
+        color::difference_degrees(hue_blended.h, hct(&blue).h)
+            < color::difference_degrees(red_hct.h, hct(&blue).h)
+    );
+    assert_eq!(color::blend_hct_hue(&red, &blue, 0.0), red);
+
+    // Tints get lighter and shades darker, at about the same hue
+    let base = rgb(103, 80, 164);
+    let tinted = color::Oklch::from_rgb(&color::tint(&base, 0.4));
+    let shaded = color::Oklch::from_rgb(&color::shade(&base, 0.4));
+    let base = color::Oklch::from_rgb(&base);
+    assert!(tinted.l > base.l && shaded.l < base.l);
+    assert!(color::difference_degrees(tinted.h, base.h) < 5.0);
+    assert!(color::difference_degrees(shaded.h, base.h) < 5.0);
+    assert_eq!(color::tint(&red, 1.0), white);
+    assert_eq!(color::shade(&red, 1.0), black);
+}
+
+#[test]
+fn test_temperature() {
+    let hct_of = |hex: &str| {
+        let rgb = color::hex_to_rgb(hex).unwrap();
+        color::rgb_to_hct(rgb.r, rgb.g, rgb.b)
+    };
+    let hexes = |colors: Vec<color::Hct>| colors.iter().map(|hct| hct.to_hex()).collect::<Vec<_>>();
+
+    // Reference values from material-color-utilities
+    assert!((color::raw_temperature(&color::Rgb { r: 0, g: 0, b: 255 }) - -1.393).abs() < 0.001);
+    assert!((color::raw_temperature(&color::Rgb { r: 255, g: 0, b: 0 }) - 2.351).abs() < 0.001);
+    assert!(
+        (color::raw_temperature(&color::Rgb {
+            r: 255,
+            g: 255,
+            b: 255
+        }) - -0.5)
+            .abs()
+            < 0.001
+    );
+
+    let blue = color::TemperatureCache::new(&hct_of("#0000ff"));
+    assert_eq!(blue.complement().to_hex(), "#9d0002");
+    assert_eq!(
+        hexes(blue.analogous_colors(5, 12)),
+        ["#00590c", "#00564e", "#0000ff", "#6700cc", "#81009f"]
+    );
+    let red = color::TemperatureCache::new(&hct_of("#ff0000"));
+    assert_eq!(red.complement().to_hex(), "#007bfc");
+    assert_eq!(
+        hexes(red.analogous_colors(5, 12)),
+        ["#f60082", "#fc004c", "#ff0000", "#d95500", "#af7200"]
+    );
+    assert!(red.relative_temperature(&hct_of("#ff0000")) > 0.9);
+    assert!(red.relative_temperature(&hct_of("#0000ff")) < 0.1);
+
+    // Without chroma there is no warmer or cooler hue
+    let white = color::TemperatureCache::new(&hct_of("#ffffff"));
+    assert_eq!(white.complement().to_hex(), "#ffffff");
+    assert_eq!(hexes(white.analogous_colors(3, 6)), ["#ffffff"; 3]);
+
+    // A theme with only a primary color gets three distinct accent hues
+    let theme_data = serde_json::json!({ "primary": "#4285f4" });
+    let palette = theme::generate_palette(&theme_data, true, false).unwrap();
+    let hues = [
+        palette.palettes.primary.hue,
+        palette.palettes.secondary.hue,
+        palette.palettes.tertiary.hue,
+    ];
+    for (a, b) in [(0, 1), (0, 2), (1, 2)] {
+        assert!(
+            color::difference_degrees(hues[a], hues[b]) > 15.0,
+            "{:?}",
+            hues
+        );
+    }
+    // A secondary from the theme is kept
+    let theme_data = serde_json::json!({ "primary": "#4285f4", "secondary": "#34a853" });
+    let palette = theme::generate_palette(&theme_data, true, false).unwrap();
+    assert!(color::difference_degrees(palette.palettes.secondary.hue, hct_of("#34a853").h) < 1.0);
+
+    // Fidelity's tertiary is the complement, content's an analogous color
+    let source = hct_of("#4285f4");
+    let cache = color::TemperatureCache::new(&source);
+    let fidelity = Scheme::Fidelity.tonal_palettes(&source);
+    assert!((fidelity.tertiary.hue - cache.complement().h).abs() < 1e-9);
+    let content = Scheme::Content.tonal_palettes(&source);
+    assert!((content.tertiary.hue - cache.analogous_colors(3, 6)[2].h).abs() < 1e-9);
+}
+
+#[test]
+fn test_dislike_analyzer() {
+    let hct_of = |hex: &str| {
+        let rgb = color::hex_to_rgb(hex).unwrap();
+        color::rgb_to_hct(rgb.r, rgb.g, rgb.b)
+    };
+
+    // Dark yellow-greens are disliked; lighter, grayer or other hues are not
+    let bile = color::Hct::from_hct(100.0, 40.0, 40.0);
+    assert!(color::is_disliked(&bile));
+    assert!(!color::is_disliked(&color::Hct::from_hct(
+        100.0, 40.0, 70.0
+    )));
+    assert!(!color::is_disliked(&color::Hct::from_hct(
+        100.0, 10.0, 40.0
+    )));
+    assert!(!color::is_disliked(&color::Hct::from_hct(
+        140.0, 40.0, 40.0
+    )));
+
+    let fixed = color::fix_if_disliked(&bile);
+    assert!((fixed.t - 70.0).abs() < 1.0);
+    assert!((fixed.h - bile.h).abs() < 2.0);
+    assert!(!color::is_disliked(&fixed));
+    let blue = hct_of("#4285f4");
+    assert_eq!(color::fix_if_disliked(&blue).to_hex(), blue.to_hex());
+
+    // Colors a theme sets are used as written
+    let gruvbox_green = hct_of("#98971a");
+    assert!(color::is_disliked(&gruvbox_green));
+    let theme_data = serde_json::json!({ "primary": "#98971a", "tertiary": bile.to_hex() });
+    let theme_data = ThemeMode::from_value(&theme_data).unwrap();
+    let palette =
+        theme::generate_palette_with_options(&theme_data, true, false, &Default::default())
+            .unwrap();
+    assert_eq!(palette.primary.default.hex, "#98971a");
+    assert_eq!(palette.tertiary.default.hex, bile.to_hex());
+
+    // Fidelity and content schemes fix their tertiary, like MD3
+    let source = color::Hct::from_hct(280.0, 40.0, 40.0);
+    let complement = color::TemperatureCache::new(&source).complement();
+    assert!(color::is_disliked(&complement));
+    let fixed = color::fix_if_disliked(&complement);
+    let tertiary = Scheme::Fidelity.tonal_palettes(&source).tertiary;
+    assert_eq!(tertiary.hex(70.0), fixed.to_hex());
+}
+
+#[test]
+fn test_palette_adjustments() {
+    let hct_of = |hex: &str| {
+        let rgb = color::parse_color(hex).unwrap().rgb();
+        color::rgb_to_hct(rgb.r, rgb.g, rgb.b)
+    };
+    let source = color::Hct::from_hct(280.0, 40.0, 50.0);
+
+    let adjustments = theme::PaletteAdjustments {
+        hue_shift: Some(100.0),
+        chroma_scale: Some(0.5),
+        tone_offset: Some(-10.0),
+    };
+    let adjusted = adjustments.apply(&source);
+    assert!((adjusted.h - 20.0).abs() < 1.0);
+    assert!((adjusted.c - 20.0).abs() < 1.0);
+    assert!((adjusted.t - 40.0).abs() < 1.0);
+    assert!(theme::PaletteAdjustments::default().is_identity());
+
+    // Unset fields fall back to the other adjustments
+    let merged = theme::PaletteAdjustments {
+        hue_shift: Some(5.0),
+        ..Default::default()
+    }
+    .or(adjustments);
+    assert_eq!(merged.hue_shift, Some(5.0));
+    assert_eq!(merged.chroma_scale, Some(0.5));
+
+    // Theme keys at the top level apply to both modes; mode keys win
+    let theme_all = serde_json::json!({
+        "hue_shift": 30,
+        "dark": { "primary": source.to_hex(), "hue_shift": 60, "custom": { "brand": "#4caf50" } },
+        "light": { "primary": source.to_hex() }
+    });
+    let generate = |mode: &str, options: &theme::PaletteOptions| {
+        let (theme_data, mode) = Theme::from_value(&theme_all).unwrap().mode(mode).unwrap();
+        theme::generate_palette_with_options(&theme_data, mode == "dark", false, options).unwrap()
+    };
+    let defaults = theme::PaletteOptions::default();
+    let hue_difference = |palette: &theme::Palette| {
+        color::difference_degrees(hct_of(&palette.primary.default.hex).h, source.h)
+    };
+    assert!((hue_difference(&generate("light", &defaults)) - 30.0).abs() < 2.0);
+    let dark = generate("dark", &defaults);
+    assert!((hue_difference(&dark) - 60.0).abs() < 2.0);
+    assert!(
+        color::difference_degrees(dark.custom[0].palette.hue, hct_of("#4caf50").h) > 30.0,
+        "custom colors are adjusted before harmonizing"
+    );
+
+    // Command line and config adjustments override the theme's
+    let options = theme::PaletteOptions {
+        adjustments: theme::PaletteAdjustments {
+            hue_shift: Some(0.0),
+            tone_offset: Some(20.0),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let light = generate("light", &options);
+    assert!(hue_difference(&light) < 2.0);
+    assert!((hct_of(&light.primary.default.hex).t - 70.0).abs() < 1.0);
+
+    // With a dynamic scheme the chroma scale reaches every tonal palette and
+    // the tone offset every role tone
+    let seed = ThemeMode::from_value(&serde_json::json!({ "primary": "#6750a4" })).unwrap();
+    let scheme_palette = |adjustments: theme::PaletteAdjustments| {
+        let options = theme::PaletteOptions {
+            scheme: Some(tinct::Scheme::TonalSpot),
+            adjustments,
+            ..Default::default()
+        };
+        theme::generate_palette_with_options(&seed, false, false, &options).unwrap()
+    };
+    let plain = scheme_palette(theme::PaletteAdjustments::default());
+    let muted = scheme_palette(theme::PaletteAdjustments {
+        chroma_scale: Some(0.5),
+        ..Default::default()
+    });
+    assert!((muted.palettes.primary.chroma - plain.palettes.primary.chroma * 0.5).abs() < 1e-9);
+    assert!((muted.palettes.neutral.chroma - plain.palettes.neutral.chroma * 0.5).abs() < 1e-9);
+    assert!(hct_of(&muted.primary.default.hex).c < hct_of(&plain.primary.default.hex).c - 5.0);
+    let lighter = scheme_palette(theme::PaletteAdjustments {
+        tone_offset: Some(10.0),
+        ..Default::default()
+    });
+    // The primary is lifted, then pulled back to keep its contrast on the lighter surfaces
+    let tone = |palette: &theme::Palette| hct_of(&palette.primary.default.hex).t;
+    assert!(tone(&lighter) > tone(&plain) + 5.0);
+    assert!((hct_of(&lighter.surface_container.default.hex).t - 100.0).abs() < 1.0);
+    assert_ne!(
+        lighter.primary_container.default.hex,
+        plain.primary_container.default.hex
+    );
+
+    // Alpha is kept and invalid keys are reported
+    let translucent = serde_json::json!({ "primary": "#6750a480", "tone_offset": 10 });
+    let palette = theme::generate_palette(&translucent, true, false).unwrap();
+    assert_eq!(palette.primary.default.alpha, 0x80);
+    let invalid = serde_json::json!({ "primary": "#6750a4", "chroma_scale": "high" });
+    assert!(theme::generate_palette(&invalid, true, false).is_err());
+
+    // A negative chroma scale is rejected the same way in a theme and a config
+    let negative = serde_json::json!({ "primary": "#6750a4", "chroma_scale": -1 });
+    let error = theme::generate_palette(&negative, true, false).unwrap_err();
+    assert_eq!(error, "Chroma scale must not be negative, got -1");
+    let config: tinct::config::Config = toml::from_str("chroma_scale = -0.5").unwrap();
+    let error = config.adjustments().validate().unwrap_err();
+    assert_eq!(error, "Chroma scale must not be negative, got -0.5");
+}
+
+#[test]
+fn test_custom_colors() {
+    // Harmonizing moves the hue halfway toward the source, by at most 15 degrees
+    let source = color::Hct::from_hct(280.0, 40.0, 50.0);
+    let near = color::harmonize(&color::Hct::from_hct(260.0, 40.0, 60.0), &source);
+    assert!((near.h - 270.0).abs() < 1.0);
+    assert!((near.t - 60.0).abs() < 0.5);
+    let far = color::harmonize(&color::Hct::from_hct(140.0, 40.0, 60.0), &source);
+    assert!((far.h - 155.0).abs() < 1.0, "{}", far.h);
+
+    let theme_all = serde_json::json!({
+        "custom": {
+            "success": { "hex": "#4caf50", "harmonize": true },
+            "git_removed": { "hex": "#e53935", "harmonize": false },
+        },
+        "dark": {
+            "primary": "#6750a4",
+            "custom": { "warning": "#ffb300" },
+        },
+        "light": { "primary": "#6750a4" },
+    });
+    let theme_all = Theme::from_value(&theme_all).unwrap();
+    let (dark_theme, _) = theme_all.mode("dark").unwrap();
+    let (light_theme, _) = theme_all.mode("light").unwrap();
+    let generate = |theme: &ThemeMode, is_dark| {
+        theme::generate_palette_with_options(theme, is_dark, false, &Default::default()).unwrap()
+    };
+    let dark = generate(&dark_theme, true);
+    let light = generate(&light_theme, false);
+    assert_eq!(dark.custom.len(), 3);
+    assert_eq!(light.custom.len(), 2);
+
+    let custom = |palette: &theme::Palette, name: &str| {
+        palette
+            .custom
+            .iter()
+            .find(|custom| custom.name == name)
+            .map(|custom| custom.palette.clone())
+            .unwrap()
+    };
+    let input_hue = |hex: &str| {
+        let rgb = color::hex_to_rgb(hex).unwrap();
+        color::rgb_to_hct(rgb.r, rgb.g, rgb.b).h
+    };
+    let primary_hue = dark.palettes.primary.hue;
+    let success = custom(&dark, "success");
+    assert!(
+        color::difference_degrees(success.hue, primary_hue)
+            < color::difference_degrees(input_hue("#4caf50"), primary_hue)
+    );
+    assert!((custom(&dark, "git_removed").hue - input_hue("#e53935")).abs() < 0.5);
+
+    // Roles use the accent tones and are reachable from templates
+    let output = theme::process_template(
+        "{{colors.success.default.hex}} {{colors.on_success.default.hex}} {{colors.success_container.default.hex}} {{colors.on_success_container.default.hex}} {{colors.palettes.success.tone_80.hex}}",
+        &dark,
+        "dark",
+    );
+    let hexes: Vec<&str> = output.split(' ').collect();
+    assert_eq!(hexes[0], success.hex(80.0));
+    assert_eq!(hexes[1], success.hex(20.0));
+    assert_eq!(hexes[2], success.hex(30.0));
+    assert_eq!(hexes[3], success.hex(90.0));
+    assert_eq!(hexes[4], success.hex(80.0));
+    assert_eq!(
+        theme::process_template("{{colors.success.default.hex}}", &light, "light"),
+        custom(&light, "success").hex(40.0)
+    );
+
+    // Custom colors also work with dynamic schemes
+    let options = theme::PaletteOptions {
+        scheme: Some(Scheme::Vibrant),
+        ..Default::default()
+    };
+    let vibrant = theme::generate_palette_with_options(&dark_theme, true, false, &options).unwrap();
+    assert_eq!(vibrant.custom.len(), 3);
+
+    // Names may not shadow built-in roles, and entries must be valid colors
+    for custom in [
+        serde_json::json!({ "surface": "#4caf50" }),
+        serde_json::json!({ "secondary_container": "#4caf50" }),
+        serde_json::json!({ "git-added": "#4caf50" }),
+        serde_json::json!({ "success": { "harmonize": true } }),
+        serde_json::json!({ "success": { "hex": "#4caf50", "harmonize": "yes" } }),
+        serde_json::json!({ "success": "notacolor" }),
+    ] {
+        let theme_data = serde_json::json!({ "primary": "#6750a4", "custom": custom });
+        assert!(
+            theme::generate_palette(&theme_data, true, false).is_err(),
+            "{}",
+            custom
+        );
+    }
+}
+
+#[test]
+fn test_ansi_palette() {
+    let hct = |color_format: &theme::ColorFormat| {
+        let rgb = color_format.rgb();
+        color::rgb_to_hct(rgb.r, rgb.g, rgb.b)
+    };
+    let canonical_hue = |r, g, b| color::rgb_to_hct(r, g, b).h;
+    let theme_data = serde_json::json!({ "primary": "#6750a4" });
+
+    for is_dark_mode in [true, false] {
+        let palette = theme::generate_palette(&theme_data, is_dark_mode, false).unwrap();
+        let ansi = &palette.ansi;
+        assert_eq!(ansi.colors().len(), 16);
+        for (index, name) in tinct::ansi::ANSI_NAMES.iter().enumerate() {
+            let by_index = ansi.get(&format!("color{}", index)).unwrap();
+            assert_eq!(ansi.get(name).unwrap().hex, by_index.hex);
+        }
+        assert!(ansi.get("color16").is_none());
+        assert!(ansi.get("orange").is_none());
+
+        // Canonical hues, harmonized by at most 15 degrees
+        for (name, hue) in [
+            ("red", canonical_hue(255, 0, 0)),
+            ("green", canonical_hue(0, 255, 0)),
+            ("blue", canonical_hue(0, 0, 255)),
+            ("bright_cyan", canonical_hue(0, 255, 255)),
+        ] {
+            let ansi_hue = hct(ansi.get(name).unwrap()).h;
+            assert!(color::difference_degrees(ansi_hue, hue) <= 16.0, "{}", name);
+        }
+
+        // Colors are readable on the surface
+        for name in ["red", "green", "yellow", "blue", "magenta", "cyan"] {
+            for name in [name.to_string(), format!("bright_{}", name)] {
+                let ratio = color::get_contrast_ratio(
+                    &ansi.get(&name).unwrap().hex,
+                    &palette.surface.default.hex,
+                )
+                .unwrap();
+                assert!(ratio >= 4.4, "{}: {:.2}", name, ratio);
+            }
+        }
+
+        // Black and white come from the neutrals
+        let black = hct(ansi.get("black").unwrap());
+        let white = hct(ansi.get("white").unwrap());
+        let bright_white = hct(ansi.get("bright_white").unwrap());
+        assert!(black.t < white.t && white.t < bright_white.t);
+        assert!(black.c < 10.0 && white.c < 10.0);
+    }
+
+    // Reachable from templates
+    let palette = theme::generate_palette(&theme_data, true, false).unwrap();
+    let output = theme::process_template(
+        "{{colors.ansi.red.hex}} {{colors.ansi.color9.hex}} {{ colors.ansi.bright_red.rgb }} {{colors.ansi.orange.hex}}",
+        &palette,
+        "dark",
+    );
+    let bright_red = palette.ansi.get("bright_red").unwrap();
+    assert_eq!(
+        output,
+        format!(
+            "{} {} {} #000000",
+            palette.ansi.get("red").unwrap().hex,
+            bright_red.hex,
+            bright_red.rgb
+        )
+    );
+
+    // Without a primary hue to harmonize toward, canonical hues are kept
+    let monochrome = theme::palette_from_seed(
+        "#6750a4",
+        true,
+        &theme::PaletteOptions {
+            scheme: Some(Scheme::Monochrome),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let red = hct(monochrome.ansi.get("red").unwrap());
+    assert!(color::difference_degrees(red.h, canonical_hue(255, 0, 0)) < 2.0);
+}
+
+#[test]
+fn test_xterm_indices() {
+    let rgb = |r, g, b| color::Rgb { r, g, b };
+
+    // The palette layout: basic colors, 6x6x6 cube, gray ramp
+    assert_eq!(color::xterm_rgb(1), rgb(0xcd, 0, 0));
+    assert_eq!(color::xterm_rgb(16), rgb(0, 0, 0));
+    assert_eq!(color::xterm_rgb(196), rgb(255, 0, 0));
+    assert_eq!(color::xterm_rgb(67), rgb(95, 135, 175));
+    assert_eq!(color::xterm_rgb(231), rgb(255, 255, 255));
+    assert_eq!(color::xterm_rgb(232), rgb(8, 8, 8));
+    assert_eq!(color::xterm_rgb(255), rgb(238, 238, 238));
+
+    // Exact palette colors map to themselves
+    for index in [16, 67, 124, 196, 226, 231, 232, 244] {
+        assert_eq!(color::nearest_ansi256(&color::xterm_rgb(index)), index);
+    }
+    assert_eq!(color::nearest_ansi256(&rgb(250, 2, 3)), 196);
+    assert_eq!(color::nearest_ansi256(&rgb(0x1e, 0x1e, 0x2e)), 234);
+    // Grays go to the gray ramp rather than a tinted cube color
+    assert_eq!(color::nearest_ansi256(&rgb(0x80, 0x80, 0x80)), 244);
+
+    assert_eq!(color::nearest_ansi16(&rgb(0, 0, 0)), 0);
+    assert_eq!(color::nearest_ansi16(&rgb(0xff, 0xff, 0xff)), 15);
+    assert_eq!(color::nearest_ansi16(&rgb(0xc0, 0x10, 0x10)), 1);
+    assert_eq!(color::nearest_ansi16(&rgb(0x20, 0xf0, 0x20)), 10);
+    assert_eq!(color::nearest_ansi16(&rgb(0x80, 0x80, 0x80)), 8);
+
+    // Every role gets the properties in templates
+    let theme_data = serde_json::json!({ "primary": "#ff0000", "surface": "#000000" });
+    let palette = theme::generate_palette(&theme_data, true, false).unwrap();
+    assert_eq!(
+        theme::process_template(
+            "{{colors.primary.default.ansi256}} {{colors.primary.default.ansi16}} {{colors.surface.default.ansi256}} {{colors.ansi.bright_white.ansi16}} {{colors.missing.default.ansi256}}",
+            &palette,
+            "dark",
+        ),
+        "196 9 16 7 16"
+    );
+}
+
+#[test]
+fn test_cvd_simulation() {
+    use color::{ColorDeficiency, CvdSimulation, Lab, Rgb};
+    let rgb = |r, g, b| Rgb { r, g, b };
+    let delta_e = |a: &Rgb, b: &Rgb| color::delta_e_2000(&Lab::from_rgb(a), &Lab::from_rgb(b));
+
+    for deficiency in [
+        ColorDeficiency::Protan,
+        ColorDeficiency::Deutan,
+        ColorDeficiency::Tritan,
+    ] {
+        // Neutral colors look the same to everyone
+        for gray in [rgb(255, 255, 255), rgb(128, 128, 128), rgb(0, 0, 0)] {
+            let simulated = color::simulate_cvd(&gray, deficiency, 1.0);
+            assert!(
+                delta_e(&gray, &simulated) < 1.0,
+                "{} {:?}",
+                deficiency,
+                simulated
+            );
+        }
+        // Severity 0 is normal vision
+        let orange = rgb(230, 120, 30);
+        assert_eq!(color::simulate_cvd(&orange, deficiency, 0.0), orange);
+    }
+
+    // Red and green get much closer for deuteranopes, but not for tritanopes
+    let (red, green) = (rgb(200, 40, 40), rgb(40, 160, 40));
+    let deutan = CvdSimulation {
+        deficiency: ColorDeficiency::Deutan,
+        severity: 1.0,
+    };
+    let partial = CvdSimulation {
+        severity: 0.5,
+        ..deutan
+    };
+    let normal_difference = delta_e(&red, &green);
+    let deutan_difference = delta_e(&deutan.apply(&red), &deutan.apply(&green));
+    let partial_difference = delta_e(&partial.apply(&red), &partial.apply(&green));
+    assert!(deutan_difference < normal_difference / 2.0);
+    assert!(deutan_difference < partial_difference && partial_difference < normal_difference);
+
+    // A red error role next to a green tertiary collapses for deuteranopes
+    let theme_data = serde_json::json!({
+        "primary": "#1565c0",
+        "tertiary": "#2e7d32",
+        "error": "#c62828",
+    });
+    let palette = theme::generate_palette(&theme_data, true, false).unwrap();
+    let collapsed = tinct::check::collapsed_role_pairs(&palette, ColorDeficiency::Deutan, 1.0);
+    assert!(collapsed
+        .iter()
+        .any(|pair| pair.first == "tertiary" && pair.second == "error"));
+    for pair in &collapsed {
+        assert!(pair.delta_e >= tinct::check::MIN_DISTINCT_DELTA_E);
+        assert!(pair.simulated_delta_e < tinct::check::MIN_DISTINCT_DELTA_E);
+    }
+}
+
+#[test]
+fn test_theme_validation() {
+    let temp_dir = TempDir::new().expect("create temp dir");
+    let load = |content: &str| {
+        let theme_path = temp_dir.path().join("theme.json");
+        std::fs::write(&theme_path, content).expect("Unable to write theme file");
+        theme::load_theme(theme_path.to_str().unwrap())
+    };
+
+    // Unknown keys and invalid colors are reported with their path
+    let error =
+        load(r##"{ "dark": { "primary": "#6750a4", "on_primry": "#ffffff" } }"##).unwrap_err();
+    assert!(error.ends_with("dark.on_primry: unknown key"), "{}", error);
+    let error = load(r##"{ "light": { "primary": "#67z0a4" } }"##).unwrap_err();
+    assert!(error.contains("light.primary: Invalid color"), "{}", error);
+    let error = load(r##"{ "dark": { "primary": 42 } }"##).unwrap_err();
+    assert!(error.contains("dark.primary: invalid type"), "{}", error);
+    let error = load(r##"{ "custom": { "success": { "hex": "#4caf50", "harmonize": "yes" } } }"##)
+        .unwrap_err();
+    assert!(error.contains("custom.success.harmonize"), "{}", error);
+    let error = load(r##"{ "drak": { "primary": "#6750a4" } }"##).unwrap_err();
+    assert!(error.ends_with("drak: unknown key"), "{}", error);
+
+    // Aliases and both custom entry forms are accepted
+    let theme_all = load(
+        r##"{
+            "custom": { "success": "#4caf50" },
+            "dark": {
+                "mPrimary": "#6750a4",
+                "mOnSurface": "rgb(230 225 229)",
+                "custom": { "warning": { "hex": "#ffb300", "harmonize": false } }
+            }
+        }"##,
+    )
+    .unwrap();
+    let parsed = tinct::theme_file::Theme::from_value(&theme_all).unwrap();
+    let dark = parsed.dark.unwrap();
+    assert_eq!(
+        dark.primary.unwrap().0,
+        color::parse_color("#6750a4").unwrap()
+    );
+    assert!(!dark.custom["warning"].harmonize);
+    assert!(parsed.custom["success"].harmonize);
+
+    // Container and surface container roles are used as written
+    let mode = serde_json::json!({
+        "primary": "#6750a4",
+        "primary_container": "#4f378b",
+        "mOnPrimaryContainer": "#eaddff",
+        "secondary_container": "#4a4458",
+        "mTertiaryContainer": "#633b48",
+        "outline_variant": "#49454f",
+        "surface_container_high": "#2b2930",
+    });
+    let palette = theme::generate_palette(&mode, true, false).unwrap();
+    assert_eq!(palette.primary_container.default.hex, "#4f378b");
+    assert_eq!(palette.on_primary_container.default.hex, "#eaddff");
+    assert_eq!(palette.secondary_container.default.hex, "#4a4458");
+    assert_eq!(palette.tertiary_container.default.hex, "#633b48");
+    assert_eq!(palette.outline_variant.default.hex, "#49454f");
+    assert_eq!(palette.surface_container_high.default.hex, "#2b2930");
+    let ratio = color::get_contrast_ratio(
+        &palette.on_secondary_container.default.hex,
+        &palette.secondary_container.default.hex,
+    )
+    .unwrap();
+    assert!(ratio >= 4.5, "{}", ratio);
+
+    // A mode on its own reports paths within the mode
+    let mode = serde_json::json!({ "primary": "#6750a4", "secondry": "#625b71" });
+    let error = theme::generate_palette(&mode, true, false).unwrap_err();
+    assert_eq!(error, "secondry: unknown key");
+
+    // Background is its own role when the theme sets it
+    let mode = serde_json::json!({ "primary": "#6750a4", "background": "#101010" });
+    let palette = theme::generate_palette(&mode, true, false).unwrap();
+    assert_eq!(palette.background.default.hex, "#101010");
+    assert_ne!(palette.surface.default.hex, "#101010");
+    let ratio = color::get_contrast_ratio(
+        &palette.on_background.default.hex,
+        &palette.background.default.hex,
+    )
+    .unwrap();
+    assert!(ratio >= 4.5);
+}
+
+#[test]
+fn test_theme_extends() {
+    let temp_dir = TempDir::new().expect("create temp dir");
+    let write = |name: &str, content: serde_json::Value| {
+        let path = temp_dir.path().join(name);
+        std::fs::write(&path, content.to_string()).expect("Unable to write theme file");
+        path.to_str().unwrap().to_string()
+    };
+    write(
+        "base.json",
+        serde_json::json!({
+            "custom": { "success": "#4caf50" },
+            "dark": { "primary": "#6750a4", "surface": "#141218" },
+            "light": { "primary": "#6750a4" },
+        }),
+    );
+
+    // Keys are deep-merged over the parent's
+    let child = write(
+        "child.json",
+        serde_json::json!({
+            "extends": "base",
+            "custom": { "warning": "#ffb300" },
+            "dark": { "primary": "#b3261e" },
+        }),
+    );
+    let theme_all = theme::load_theme(&child).unwrap();
+    assert_eq!(theme_all["dark"]["primary"], "#b3261e");
+    assert_eq!(theme_all["dark"]["surface"], "#141218");
+    assert_eq!(theme_all["light"]["primary"], "#6750a4");
+    assert_eq!(theme_all["custom"]["success"], "#4caf50");
+    assert_eq!(theme_all["custom"]["warning"], "#ffb300");
+    assert!(theme_all.get("extends").is_none());
+
+    // A mode extends the same mode of its parent
+    let child = write(
+        "mode_child.json",
+        serde_json::json!({
+            "dark": { "extends": "base.json", "secondary": "#625b71" },
+            "light": { "primary": "#00639b" },
+        }),
+    );
+    let theme_all = theme::load_theme(&child).unwrap();
+    assert_eq!(theme_all["dark"]["primary"], "#6750a4");
+    assert_eq!(theme_all["dark"]["secondary"], "#625b71");
+    assert!(theme_all["dark"].get("extends").is_none());
+    assert!(theme_all.get("custom").is_none());
+    assert_eq!(theme_all["light"]["primary"], "#00639b");
+
+    // Cycles, missing parents and invalid parents are reported with the chain
+    write("loop_a.json", serde_json::json!({ "extends": "loop_b" }));
+    let loop_b = write("loop_b.json", serde_json::json!({ "extends": "loop_a" }));
+    let error = theme::load_theme(&loop_b).unwrap_err();
+    assert!(error.contains("Theme inheritance cycle"), "{}", error);
+    assert!(error.contains("loop_b.json' -> '"), "{}", error);
+
+    let orphan = write(
+        "orphan.json",
+        serde_json::json!({ "extends": "no-such-theme" }),
+    );
+    let error = theme::load_theme(&orphan).unwrap_err();
+    assert!(
+        error.contains("Theme 'no-such-theme' not found"),
+        "{}",
+        error
+    );
+    assert!(error.contains("extended by"), "{}", error);
+
+    write(
+        "broken.json",
+        serde_json::json!({ "dark": { "primary": "#zzzzzz" } }),
+    );
+    let middle = write("middle.json", serde_json::json!({ "extends": "broken" }));
+    let top = write("top.json", serde_json::json!({ "extends": "middle" }));
+    let error = theme::load_theme(&top).unwrap_err();
+    let lines: Vec<&str> = error.lines().collect();
+    assert!(lines[0].contains("broken.json': dark.primary"), "{}", error);
+    assert!(lines[1].contains(&middle), "{}", error);
+    assert!(lines[2].contains(&top), "{}", error);
+
+    write(
+        "light_base.json",
+        serde_json::json!({ "light": { "primary": "#6750a4" } }),
+    );
+    let dark_child = write(
+        "dark_child.json",
+        serde_json::json!({ "dark": { "extends": "light_base" } }),
+    );
+    let error = theme::load_theme(&dark_child).unwrap_err();
+    assert!(error.contains("has no 'dark' mode"), "{}", error);
+}
+
+#[test]
+fn test_theme_formats() {
+    let temp_dir = TempDir::new().expect("create temp dir");
+    let write = |name: &str, content: &str| {
+        let path = temp_dir.path().join(name);
+        std::fs::write(&path, content).expect("Unable to write theme file");
+        path.to_str().unwrap().to_string()
+    };
+    let expected = serde_json::json!({
+        "hue_shift": 10,
+        "custom": { "success": { "hex": "#4caf50", "harmonize": false } },
+        "dark": { "primary": "#d0bcff", "surface": "#141218" },
+        "light": { "primary": "#6750a4" },
+    });
+
+    let toml_theme = write(
+        "theme.toml",
+        r##"
+hue_shift = 10
+
+[custom.success]
+hex = "#4caf50"
+harmonize = false
+
+[dark]
+primary = "#d0bcff"
+surface = "#141218"
+
+[light]
+primary = "#6750a4"
+"##,
+    );
+    assert_eq!(theme::load_theme(&toml_theme).unwrap(), expected);
+
+    let yaml_theme = write(
+        "theme.YML",
+        r##"
+hue_shift: 10
+custom:
+  success: { hex: "#4caf50", harmonize: false }
+dark:
+  primary: "#d0bcff"
+  surface: "#141218"
+light:
+  primary: "#6750a4"
+"##,
+    );
+    assert_eq!(theme::load_theme(&yaml_theme).unwrap(), expected);
+
+    // Parents are found by name with any extension, in any format
+    write(
+        "base.yaml",
+        "dark:\n  primary: \"#6750a4\"\n  surface: \"#141218\"\n",
+    );
+    let child = write(
+        "child.toml",
+        "extends = \"base\"\n\n[dark]\nprimary = \"#b3261e\"\n",
+    );
+    let theme_all = theme::load_theme(&child).unwrap();
+    assert_eq!(theme_all["dark"]["primary"], "#b3261e");
+    assert_eq!(theme_all["dark"]["surface"], "#141218");
+
+    // Errors name the format, and validation applies to every format
+    let broken = write("broken.yaml", "dark: [unclosed");
+    let error = theme::load_theme(&broken).unwrap_err();
+    assert!(error.starts_with("Invalid YAML format"), "{}", error);
+    let broken = write("broken.toml", "[dark\nprimary = 1");
+    let error = theme::load_theme(&broken).unwrap_err();
+    assert!(error.starts_with("Invalid TOML format"), "{}", error);
+    let typo = write("typo.yaml", "dark:\n  primry: \"#6750a4\"\n");
+    let error = theme::load_theme(&typo).unwrap_err();
+    assert!(error.ends_with("dark.primry: unknown key"), "{}", error);
+}
+
+#[test]
+fn test_base16() {
+    let temp_dir = TempDir::new().expect("create temp dir");
+    let write = |name: &str, content: &str| {
+        let path = temp_dir.path().join(name);
+        std::fs::write(&path, content).expect("Unable to write scheme file");
+        path.to_str().unwrap().to_string()
+    };
+
+    // Classic flat scheme, hex without '#', variant from the tone of base00
+    let classic_scheme = r##"
+scheme: "Default Dark"
+author: "Chris Kempson"
+base00: "181818"
+base01: "282828"
+base02: "383838"
+base03: "585858"
+base04: "b8b8b8"
+base05: "d8d8d8"
+base06: "e8e8e8"
+base07: "f8f8f8"
+base08: "ab4642"
+base09: "dc9656"
+base0A: "f7ca88"
+base0B: "a1b56c"
+base0C: "86c1b9"
+base0D: "7cafc2"
+base0E: "ba8baf"
+base0F: "a16946"
+"##;
+    let theme_all = theme::load_theme(&write("default-dark.yaml", classic_scheme)).unwrap();
+    assert!(theme_all.get("light").is_none());
+    let dark = &theme_all["dark"];
+    assert_eq!(dark["primary"], "#7cafc2");
+    assert_eq!(dark["secondary"], "#86c1b9");
+    assert_eq!(dark["tertiary"], "#ba8baf");
+    assert_eq!(dark["error"], "#ab4642");
+    assert_eq!(dark["surface"], "#181818");
+    assert_eq!(dark["on_surface"], "#d8d8d8");
+    assert_eq!(dark["ansi"]["black"], "#181818");
+    assert_eq!(dark["ansi"]["bright_black"], "#585858");
+    assert_eq!(dark["ansi"]["bright_red"], "#ab4642");
+    assert_eq!(dark["ansi"]["bright_white"], "#f8f8f8");
+
+    // A dark-only scheme is used for light mode too
+    let (light, effective_mode) = theme::select_theme_mode(&theme_all, "light").unwrap();
+    assert_eq!(effective_mode, "dark");
+    let palette = theme::generate_palette(&light, true, false).unwrap();
+    assert_eq!(palette.surface.default.hex, "#181818");
+    assert_eq!(palette.ansi.get("red").unwrap().hex, "#ab4642");
+
+    // Tinted-theming base24 scheme with its own bright colors
+    let mut base24 = String::from("system: base24\nname: Test\nvariant: light\npalette:\n");
+    for (i, slot) in base16::BASE16_SLOTS
+        .iter()
+        .chain(base16::BASE24_SLOTS.iter())
+        .enumerate()
+    {
+        base24.push_str(&format!(
+            "  {}: \"#{:02x}{:02x}{:02x}\"\n",
+            slot,
+            0xf0 - i,
+            i,
+            i
+        ));
+    }
+    let theme_all = theme::load_theme(&write("base24.yaml", &base24)).unwrap();
+    let light = &theme_all["light"];
+    assert_eq!(light["primary"], "#e30d0d");
+    assert_eq!(light["ansi"]["white"], "#ea0606");
+    assert_eq!(light["ansi"]["bright_black"], "#ee0202");
+    assert_eq!(light["ansi"]["bright_red"], "#de1212");
+    assert_eq!(light["ansi"]["bright_cyan"], "#db1515");
+
+    // Invalid slots are reported by name
+    let broken = write("broken.yaml", &classic_scheme.replace("282828", "nope"));
+    let error = theme::load_theme(&broken).unwrap_err();
+    assert!(error.contains("Invalid base16 scheme"), "{}", error);
+    assert!(error.contains("base01"), "{}", error);
+
+    // Export writes every base16 slot and loads back as a theme
+    let theme = serde_json::json!({ "primary": "#6750a4" });
+    let palette = theme::generate_palette(&theme, true, false).unwrap();
+    let yaml = base16::to_base16_yaml(&palette, "exported", true).unwrap();
+    let scheme: serde_json::Value = serde_norway::from_str(&yaml).unwrap();
+    assert_eq!(scheme["system"], "base16");
+    assert_eq!(scheme["name"], "exported");
+    assert_eq!(scheme["variant"], "dark");
+    assert_eq!(scheme["palette"].as_object().unwrap().len(), 16);
+    assert_eq!(scheme["palette"]["base0D"], palette.primary.default.hex);
+    assert_eq!(scheme["palette"]["base00"], palette.surface.default.hex);
+    let theme_all = theme::load_theme(&write("exported.yaml", &yaml)).unwrap();
+    assert_eq!(theme_all["dark"]["primary"], palette.primary.default.hex);
+}
+
+#[test]
+fn test_pywal() {
+    let temp_dir = TempDir::new().expect("create temp dir");
+
+    // The cache holds the special colors and the ANSI colors, in order
+    let theme = serde_json::json!({ "primary": "#6750a4" });
+    let palette = theme::generate_palette(&theme, true, false).unwrap();
+    let colors = pywal::PywalColors::from_palette(&palette).unwrap();
+    assert_eq!(colors.background, palette.surface.default.hex);
+    assert_eq!(colors.foreground, palette.on_surface.default.hex);
+    assert_eq!(colors.colors.len(), 16);
+    assert_eq!(colors.colors[1], palette.ansi.get("red").unwrap().hex);
+    assert_eq!(
+        colors.colors[15],
+        palette.ansi.get("bright_white").unwrap().hex
+    );
+
+    let cache_dir = temp_dir.path().join("wal");
+    colors.write_cache(&cache_dir, "/tmp/wall.png").unwrap();
+    let json = std::fs::read_to_string(cache_dir.join("colors.json")).unwrap();
+    assert!(json.find("\"color9\"").unwrap() < json.find("\"color10\"").unwrap());
+    let data: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(data["wallpaper"], "/tmp/wall.png");
+    assert_eq!(data["special"]["background"], palette.surface.default.hex);
+    assert_eq!(
+        data["colors"]["color4"],
+        palette.ansi.get("blue").unwrap().hex
+    );
+    let shell = std::fs::read_to_string(cache_dir.join("colors.sh")).unwrap();
+    assert!(shell.contains("wallpaper='/tmp/wall.png'\n"));
+    assert!(shell.contains(&format!("background='{}'\n", palette.surface.default.hex)));
+    assert!(shell.contains(&format!("color15='{}'\n", colors.colors[15])));
+
+    // Values are quoted so that sourcing colors.sh never runs them
+    let shell = colors.to_shell("/tmp/it's'; touch pwned; '.png");
+    assert!(shell.contains("wallpaper='/tmp/it'\\''s'\\''; touch pwned; '\\''.png'\n"));
+
+    // A pywal colors.json loads as a theme of the mode its background calls for
+    let light_colors = r##"{
+  "wallpaper": "/tmp/wall.png",
+  "alpha": "100",
+  "special": { "background": "#f4efe6", "foreground": "#2b2b2b", "cursor": "#2b2b2b" },
+  "colors": {
+    "color0": "#f4efe6", "color1": "#a35a3a", "color2": "#5d7a4a", "color3": "#b08a2e",
+    "color4": "#3f6a8a", "color5": "#8a4f7a", "color6": "#3a7f7f", "color7": "#2b2b2b",
+    "color8": "#8f8a82", "color9": "#a35a3a", "color10": "#5d7a4a", "color11": "#b08a2e",
+    "color12": "#3f6a8a", "color13": "#8a4f7a", "color14": "#3a7f7f", "color15": "#2b2b2b"
+  }
+}"##;
+    let path = temp_dir.path().join("colors.json");
+    std::fs::write(&path, light_colors).unwrap();
+    let theme_all = theme::load_theme(path.to_str().unwrap()).unwrap();
+    assert!(theme_all.get("dark").is_none());
+    let light = &theme_all["light"];
+    assert_eq!(light["primary"], "#a35a3a");
+    assert_eq!(light["secondary"], "#5d7a4a");
+    assert_eq!(light["surface"], "#f4efe6");
+    assert_eq!(light["on_surface"], "#2b2b2b");
+    assert_eq!(light["outline"], "#8f8a82");
+    let palette = theme::generate_palette(light, false, false).unwrap();
+    assert_eq!(palette.surface.default.hex, "#f4efe6");
+    assert_eq!(palette.ansi.get("color12").unwrap().hex, "#3f6a8a");
+
+    // Missing colors are reported by name
+    let broken = temp_dir.path().join("broken.json");
+    std::fs::write(
+        &broken,
+        light_colors.replace("\"color8\": \"#8f8a82\",", ""),
+    )
+    .unwrap();
+    let error = theme::load_theme(broken.to_str().unwrap()).unwrap_err();
+    assert!(error.contains("Invalid pywal colors"), "{}", error);
+    assert!(error.ends_with("colors.color8 is missing"), "{}", error);
+}
+
 #[test]
 fn test_load_theme() {
     let temp_dir = TempDir::new().expect("create temp dir");
@@ -230,4 +1966,4 @@ fn test_save_output() {
     // Verify the file was written correctly
     let written_content = std::fs::read_to_string(output_path).unwrap();
     assert_eq!(written_content, content);
-}
\ No newline at end of file
+}